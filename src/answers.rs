@@ -0,0 +1,198 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    fs,
+    io,
+    path::Path,
+};
+
+use crate::Answer;
+
+/// The file, relative to the working directory, that known answers are kept in.
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// Known good answers, keyed by day and part.
+///
+/// Stored as a small subset of TOML, one table per day:
+///
+/// ```toml
+/// [day_08]
+/// part1 = "1087"
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    entries: BTreeMap<(u32, u8), String>,
+}
+
+/// The outcome of comparing an answer against the stored one.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String, actual: String },
+    Missing,
+}
+
+impl Answers {
+    /// Load answers from a file. A file that doesn't exist yet has no answers.
+    pub fn load(path: impl AsRef<Path>) -> Result<Answers, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Answers::parse(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn parse(contents: &str) -> Result<Answers, Box<dyn Error>> {
+        let mut answers = Answers::default();
+        let mut day: Option<u32> = None;
+
+        for (index, raw_line) in contents.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let number = table.trim().strip_prefix("day_").and_then(|d| d.parse().ok());
+                if number.is_none() {
+                    return Err(format!("line {}: expected [day_N], found {}", line_number, line).into());
+                }
+                day = number;
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(format!("line {}: expected key = value", line_number).into()),
+            };
+            let day = day.ok_or_else(|| format!("line {}: {} is outside a [day_N] table", line_number, key))?;
+            let part = match key {
+                "part1" => 1,
+                "part2" => 2,
+                _ => return Err(format!("line {}: unknown key {}", line_number, key).into()),
+            };
+            let value = unquote(value).ok_or_else(|| format!("line {}: invalid value {}", line_number, value))?;
+
+            answers.entries.insert((day, part), value);
+        }
+
+        Ok(answers)
+    }
+
+    pub fn get(&self, day: u32, part: u8) -> Option<&str> {
+        self.entries.get(&(day, part)).map(|s| s.as_str())
+    }
+
+    pub fn set(&mut self, day: u32, part: u8, answer: &Answer) {
+        self.entries.insert((day, part), answer.to_string());
+    }
+
+    pub fn check(&self, day: u32, part: u8, answer: &Answer) -> Verdict {
+        let actual = answer.to_string();
+        match self.get(day, part) {
+            None => Verdict::Missing,
+            Some(expected) if expected == actual => Verdict::Pass,
+            Some(expected) => Verdict::Fail { expected: expected.to_string(), actual },
+        }
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut current_day = None;
+        for ((day, part), value) in &self.entries {
+            if current_day != Some(*day) {
+                if current_day.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day_{:02}]", day)?;
+                current_day = Some(*day);
+            }
+            writeln!(f, "part{} = {}", part, quote(value))?;
+        }
+        Ok(())
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+fn unquote(value: &str) -> Option<String> {
+    let inner = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner,
+        // Bare integers are allowed too
+        None => return value.parse::<i64>().ok().map(|n| n.to_string()),
+    };
+
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                '\\' => out.push('\\'),
+                '"' => out.push('"'),
+                'n' => out.push('\n'),
+                _ => return None,
+            },
+            '"' => return None,
+            _ => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_will_read_tables() {
+        let answers = Answers::parse("# Known answers\n[day_01]\npart1 = \"514579\"\npart2 = 241861950\n\n[day_8]\npart1 = \"a \\\"b\\\"\"\n").unwrap();
+        assert_eq!(answers.get(1, 1), Some("514579"));
+        assert_eq!(answers.get(1, 2), Some("241861950"));
+        assert_eq!(answers.get(8, 1), Some("a \"b\""));
+        assert_eq!(answers.get(8, 2), None);
+    }
+
+    #[test]
+    fn test_parse_when_key_outside_table_will_return_err() {
+        assert!(Answers::parse("part1 = \"1\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_when_unknown_key_will_return_err() {
+        assert!(Answers::parse("[day_1]\npart3 = \"1\"\n").is_err());
+    }
+
+    #[test]
+    fn test_to_string_will_round_trip() {
+        let mut answers = Answers::default();
+        answers.set(8, 1, &Answer::Number(1087));
+        answers.set(1, 2, &Answer::Text("a \"quoted\" \\ multi\nline answer".to_string()));
+        answers.set(1, 1, &Answer::Number(-5));
+
+        let written = answers.to_string();
+        assert!(written.starts_with("[day_01]\npart1 = \"-5\"\n"));
+        assert_eq!(Answers::parse(&written).unwrap(), answers);
+    }
+
+    #[test]
+    fn test_check_will_return_verdict() {
+        let mut answers = Answers::default();
+        answers.set(1, 1, &Answer::Number(7));
+
+        assert_eq!(answers.check(1, 1, &Answer::Number(7)), Verdict::Pass);
+        assert_eq!(
+            answers.check(1, 1, &Answer::Number(8)),
+            Verdict::Fail { expected: "7".to_string(), actual: "8".to_string() }
+        );
+        assert_eq!(answers.check(1, 2, &Answer::Number(7)), Verdict::Missing);
+    }
+}
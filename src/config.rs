@@ -0,0 +1,498 @@
+use std::{fmt, str::FromStr};
+
+const USAGE: &str = "\
+Usage: advent <command> [options]
+
+Commands:
+    run      Run a single day
+    all      Run every day and print a summary table
+    bench    Time the parse and solve phases of a single day
+    check    Compare every day's answers against answers.toml
+    list     List the available days
+    help     Show this message
+
+Options:
+    -d, --day <N>          Day to run, 1 to 25 (required by run and bench)
+    -p, --part <1|2>       Only run the given part
+    -i, --input <FILE>     Puzzle input file instead of the day's default input, or -
+                           for stdin. Repeat to run against several inputs
+        --input-dir <DIR>  Directory holding input_NN.txt files, overriding
+                           $ADVENT_INPUT_DIR and the defaults of src/inputs and inputs
+    -f, --format <FMT>     Output format for run and all: text, json or csv. json
+                           writes one object per line
+        --update           Record new or changed answers (check only)
+        --warmup <N>       Untimed runs before measuring (bench only, default 3)
+    -n, --iterations <N>   Timed runs to take statistics over (bench only, default 10)
+    -h, --help             Show this message";
+
+/// A day option such as `--k <N>`, as listed in a day's `OPTIONS`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayOption {
+    /// The option's name without the leading `--`.
+    pub name: &'static str,
+    /// How the value is shown in the usage, such as `<N>`, or `None` for a
+    /// switch that doesn't take one.
+    pub value: Option<&'static str>,
+    /// What the option does, one line of the usage per line.
+    pub help: &'static str,
+}
+
+impl DayOption {
+    pub const fn value(name: &'static str, value: &'static str, help: &'static str) -> DayOption {
+        DayOption { name, value: Some(value), help }
+    }
+
+    pub const fn switch(name: &'static str, help: &'static str) -> DayOption {
+        DayOption { name, value: None, help }
+    }
+}
+
+/// The usage message, listing the options each day takes.
+pub fn usage(days: &[(u32, &[DayOption])]) -> String {
+    let mut usage = USAGE.to_string();
+    if days.iter().all(|(_, options)| options.is_empty()) {
+        return usage;
+    }
+
+    usage.push_str("\n\nDay options (run and bench only):");
+    let indent = " ".repeat(27);
+    for (day, options) in days {
+        for option in options.iter() {
+            let mut flag = format!("--{}", option.name);
+            if let Some(value) = option.value {
+                flag = format!("{} {}", flag, value);
+            }
+            // Long flags get their help on the next line
+            usage.push_str(&format!("\n        {}", flag));
+            if flag.len() < 19 {
+                usage.push_str(&" ".repeat(19 - flag.len()));
+            } else {
+                usage.push_str(&format!("\n{}", indent));
+            }
+
+            let mut lines = option.help.lines();
+            usage.push_str(&format!("Day {}: {}", day, lines.next().unwrap_or("")));
+            for line in lines {
+                usage.push_str(&format!("\n{}{}", indent, line));
+            }
+        }
+    }
+    usage
+}
+
+/// The highest day number in an Advent of Code calendar.
+pub const LAST_DAY: u32 = 25;
+
+pub const DEFAULT_WARMUP: usize = 3;
+pub const DEFAULT_ITERATIONS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Run,
+    All,
+    Bench,
+    Check,
+    List,
+    Help,
+}
+
+impl Command {
+    fn parse(name: &str) -> Result<Command, String> {
+        match name {
+            "run" => Ok(Command::Run),
+            "all" => Ok(Command::All),
+            "bench" => Ok(Command::Bench),
+            "check" => Ok(Command::Check),
+            "list" => Ok(Command::List),
+            "help" | "-h" | "--help" => Ok(Command::Help),
+            _ => Err(format!("Unrecognised command: {}", name)),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Command::Run => "run",
+            Command::All => "all",
+            Command::Bench => "bench",
+            Command::Check => "check",
+            Command::List => "list",
+            Command::Help => "help",
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unrecognised format: {} (expected text, json or csv)", name)),
+        }
+    }
+}
+
+/// Options only some days understand, such as `--k 4`, kept in the order given
+/// and named without the leading `--`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    values: Vec<(String, String)>,
+}
+
+impl Options {
+    pub fn push(&mut self, name: &str, value: String) {
+        self.values.push((name.to_string(), value));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names().any(|n| n == name)
+    }
+
+    /// The last value given for an option.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_all(name).pop()
+    }
+
+    /// Every value given for an option, for options that can be repeated.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.values.iter().filter(|(n, _)| n == name).map(|(_, value)| value.as_str()).collect()
+    }
+
+    /// The last value given for an option, parsed as a `T`.
+    pub fn parse<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        match self.get(name) {
+            Some(value) => value.parse().map(Some).map_err(|_| format!("Invalid --{}: {}", name, value)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Everything the binary has been asked to do, parsed from the command line.
+/// Day options are recognised from `day_options`, the options of every day.
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    pub command: Command,
+    pub day: Option<u32>,
+    pub part: Option<u8>,
+    pub inputs: Vec<String>,
+    pub input_dir: Option<String>,
+    pub format: Format,
+    pub update: bool,
+    pub warmup: usize,
+    pub iterations: usize,
+    pub options: Options,
+}
+
+impl Config {
+    pub fn new(args: &[String], day_options: &[DayOption]) -> Result<Config, String> {
+        let mut config = Config {
+            command: Command::Help,
+            day: None,
+            part: None,
+            inputs: vec![],
+            input_dir: None,
+            format: Format::Text,
+            update: false,
+            warmup: DEFAULT_WARMUP,
+            iterations: DEFAULT_ITERATIONS,
+            options: Options::default(),
+        };
+        let mut bench_flag = None;
+
+        let mut args = args.iter().skip(1);
+        match args.next() {
+            Some(name) => config.command = Command::parse(name)?,
+            None => return Err("A command is required".to_string()),
+        }
+
+        while let Some(arg) = args.next() {
+            // Accept both "--day 8" and "--day=8"
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline_value.clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| format!("{} requires a value", flag))
+            };
+
+            match flag {
+                "-d" | "--day" => config.day = Some(parse_day(&value()?)?),
+                "-p" | "--part" => config.part = Some(parse_part(&value()?)?),
+                "-i" | "--input" => config.inputs.push(value()?),
+                "--input-dir" => config.input_dir = Some(value()?),
+                "-f" | "--format" => config.format = Format::parse(&value()?)?,
+                "--update" => config.update = true,
+                "--warmup" => {
+                    config.warmup = parse_count(flag, &value()?, 0)?;
+                    bench_flag = Some(flag);
+                }
+                "-n" | "--iterations" => {
+                    config.iterations = parse_count(flag, &value()?, 1)?;
+                    bench_flag = Some(flag);
+                }
+                "-h" | "--help" => config.command = Command::Help,
+                _ => {
+                    let option = flag.strip_prefix("--")
+                        .and_then(|name| day_options.iter().find(|option| option.name == name))
+                        .ok_or_else(|| format!("Unrecognised argument: {}", arg))?;
+                    if option.value.is_some() {
+                        config.options.push(option.name, value()?);
+                    } else if inline_value.is_some() {
+                        return Err(format!("{} does not take a value", flag));
+                    } else {
+                        config.options.push(option.name, String::new());
+                    }
+                }
+            }
+        }
+
+        if let Some(flag) = bench_flag {
+            if config.command != Command::Bench {
+                return Err(format!("{} is only allowed with bench", flag));
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let command = self.command;
+        match command {
+            Command::Help => return Ok(()),
+            Command::Run | Command::Bench => {
+                if self.day.is_none() {
+                    return Err(format!("{} requires --day", command));
+                }
+                if !self.inputs.is_empty() && self.input_dir.is_some() {
+                    return Err("--input and --input-dir can't be used together".to_string());
+                }
+                if self.inputs.iter().filter(|i| *i == "-").count() > 1 {
+                    return Err("stdin can only be given as an input once".to_string());
+                }
+            }
+            Command::All | Command::Check | Command::List => {
+                if !self.inputs.is_empty() {
+                    return Err(format!("{} uses each day's default input, --input is not allowed", command));
+                }
+            }
+        }
+
+        if command == Command::List && (self.day.is_some() || self.part.is_some() || self.input_dir.is_some()) {
+            return Err("list does not take --day, --part or --input-dir".to_string());
+        }
+        if command == Command::All && self.day.is_some() {
+            return Err("all runs every day, --day is not allowed".to_string());
+        }
+        if self.format != Format::Text && !matches!(command, Command::Run | Command::All) {
+            return Err(format!("{} only supports --format text", command));
+        }
+        if let Some(name) = self.options.names().next() {
+            if !matches!(command, Command::Run | Command::Bench) {
+                return Err(format!("--{} is only allowed with run or bench", name));
+            }
+        }
+        if command != Command::Check && self.update {
+            return Err("--update is only allowed with check".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_day(value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(day) if (1..=LAST_DAY).contains(&day) => Ok(day),
+        _ => Err(format!("Invalid day: {} (expected 1 to {})", value, LAST_DAY)),
+    }
+}
+
+fn parse_count(flag: &str, value: &str, minimum: usize) -> Result<usize, String> {
+    match value.parse() {
+        Ok(count) if count >= minimum => Ok(count),
+        _ => Err(format!("Invalid {}: {} (expected a whole number of at least {})", flag, value, minimum)),
+    }
+}
+
+fn parse_part(value: &str) -> Result<u8, String> {
+    match value {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("Invalid part: {} (expected 1 or 2)", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_OPTIONS: &[DayOption] = &[
+        DayOption::value("k", "<N>", "find N entries"),
+        DayOption::value("target", "<N>", "the sum to find"),
+        DayOption::switch("fewest", "find the fewest entries"),
+    ];
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("advent").chain(line.split_whitespace()).map(String::from).collect()
+    }
+
+    #[test]
+    fn test_new_when_run_will_return_config() {
+        let config = Config::new(&args("run --day 8 --part=1 -i input.txt"), DAY_OPTIONS).unwrap();
+        assert_eq!(config.command, Command::Run);
+        assert_eq!(config.day, Some(8));
+        assert_eq!(config.part, Some(1));
+        assert_eq!(config.inputs, vec!["input.txt".to_string()]);
+        assert_eq!(config.format, Format::Text);
+    }
+
+    #[test]
+    fn test_new_when_invalid_day_will_return_err() {
+        for day in &["0", "26", "foo", "-1"] {
+            let line = format!("run --input x --day {}", day);
+            assert!(Config::new(&args(&line), DAY_OPTIONS).is_err());
+        }
+    }
+
+    #[test]
+    fn test_new_when_invalid_part_will_return_err() {
+        assert!(Config::new(&args("run --day 1 --input x --part 3"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_several_inputs_will_keep_them_in_order() {
+        let config = Config::new(&args("run --day 8 -i a.txt --input - --input=b.txt"), DAY_OPTIONS).unwrap();
+        assert_eq!(config.inputs, vec!["a.txt".to_string(), "-".to_string(), "b.txt".to_string()]);
+    }
+
+    #[test]
+    fn test_new_when_stdin_given_twice_will_return_err() {
+        assert!(Config::new(&args("run --day 8 -i - -i -"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_input_dir_will_return_config() {
+        let config = Config::new(&args("check --input-dir=elsewhere"), DAY_OPTIONS).unwrap();
+        assert_eq!(config.input_dir, Some("elsewhere".to_string()));
+        assert!(Config::new(&args("run --day 1 --input x --input-dir elsewhere"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_run_without_day_will_return_err() {
+        assert!(Config::new(&args("run --input x"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_flag_missing_value_will_return_err() {
+        assert!(Config::new(&args("run --input x --day"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_unknown_argument_will_return_err() {
+        assert!(Config::new(&args("all --foo"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args("frobnicate"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args(""), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_update_outside_check_will_return_err() {
+        assert!(Config::new(&args("all --update"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args("check --update"), DAY_OPTIONS).unwrap().update);
+    }
+
+    #[test]
+    fn test_new_when_format_will_return_format() {
+        assert_eq!(Config::new(&args("all --format json"), DAY_OPTIONS).unwrap().format, Format::Json);
+        assert_eq!(Config::new(&args("run -d 1 -f csv"), DAY_OPTIONS).unwrap().format, Format::Csv);
+        assert!(Config::new(&args("all --format xml"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args("check --format json"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_bench_will_return_counts() {
+        let config = Config::new(&args("bench --day 8"), DAY_OPTIONS).unwrap();
+        assert_eq!(config.warmup, DEFAULT_WARMUP);
+        assert_eq!(config.iterations, DEFAULT_ITERATIONS);
+
+        let config = Config::new(&args("bench --day 8 --warmup 0 -n 100"), DAY_OPTIONS).unwrap();
+        assert_eq!(config.warmup, 0);
+        assert_eq!(config.iterations, 100);
+    }
+
+    #[test]
+    fn test_new_when_invalid_counts_will_return_err() {
+        assert!(Config::new(&args("bench --day 8 -n 0"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args("bench --day 8 --warmup -1"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args("run --day 8 -n 5"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_new_when_day_options_will_keep_them() {
+        let config = Config::new(&args("run --day 1 --k 4 --target=100 --k 5"), DAY_OPTIONS).unwrap();
+        assert_eq!(config.options.get("k"), Some("5"));
+        assert_eq!(config.options.get_all("k"), vec!["4", "5"]);
+        assert_eq!(config.options.parse::<i32>("target"), Ok(Some(100)));
+        assert_eq!(config.options.parse::<i32>("missing"), Ok(None));
+        assert!(config.options.parse::<usize>("k").is_ok());
+        assert!(Config::new(&args("all --k 4"), DAY_OPTIONS).is_err());
+
+        let config = Config::new(&args("run --day 1 --fewest"), DAY_OPTIONS).unwrap();
+        assert!(config.options.contains("fewest"));
+        assert!(!config.options.contains("k"));
+    }
+
+    #[test]
+    fn test_new_when_switch_given_value_will_return_err() {
+        assert!(Config::new(&args("run --day 1 --fewest=yes"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args("run --day 1 --torus"), DAY_OPTIONS).is_err());
+        assert!(Config::new(&args("run --day 1 --k"), DAY_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn test_usage_will_list_day_options() {
+        assert_eq!(usage(&[(1, &[])]), USAGE);
+
+        let options = [DayOption::value("combinations", "<FMT>", "list them\nas a table"), DAY_OPTIONS[2]];
+        let expected = format!(
+            "{}\n\nDay options (run and bench only):\n{}\n{}\n{}\n{}",
+            USAGE,
+            "        --combinations <FMT>",
+            "                           Day 1: list them",
+            "                           as a table",
+            "        --fewest           Day 1: find the fewest entries",
+        );
+        assert_eq!(usage(&[(1, &options)]), expected);
+    }
+
+    #[test]
+    fn test_options_parse_when_invalid_will_return_err() {
+        let config = Config::new(&args("run --day 1 --k four"), DAY_OPTIONS).unwrap();
+        assert_eq!(config.options.parse::<usize>("k"), Err("Invalid --k: four".to_string()));
+    }
+
+    #[test]
+    fn test_new_when_help_flag_will_return_help() {
+        assert_eq!(Config::new(&args("run --help"), DAY_OPTIONS).unwrap().command, Command::Help);
+        assert_eq!(Config::new(&args("--help"), DAY_OPTIONS).unwrap().command, Command::Help);
+    }
+}
@@ -1,74 +1,605 @@
-use rand::{
-    thread_rng,
-    seq::SliceRandom,
-};
-use std::error::Error;
-
-fn find_sum_2(numbers: &[String], desired: i32) -> Option<(i32, i32)> {
-    for win in numbers.windows(2) {
-        let a: i32 = win[0].parse().unwrap();
-        let b: i32 = win[1].parse().unwrap();
-        if a + b == desired {
+use std::collections::{HashMap, HashSet};
+
+use advent::{AdventError, Answer, DayOption, Options, Solution};
+
+const DESIRED: i64 = 2020;
+
+// Largest table the fewest entries search will build
+const MAX_TABLE_SIZE: usize = 10_000_000;
+
+// Most words of sums the subset sum search will update, and the most memory
+// its copies of them may take, before meeting in the middle instead
+const MAX_SUBSET_SUM_WORK: u128 = 1 << 31;
+const MAX_SUBSET_SUM_BYTES: usize = 1 << 27;
+
+// Most combinations the meet in the middle search will hash at once
+const MAX_HASHED_COMBINATIONS: usize = 1_000_000;
+
+/// Find two entries that add up to `desired`, in the order they appear.
+fn find_sum_2(numbers: &[i64], desired: i64) -> Option<(i64, i64)> {
+    let mut seen = HashSet::new();
+    for &b in numbers {
+        // Nothing in the report can make up a difference that doesn't fit
+        let a = match desired.checked_sub(b) {
+            Some(a) => a,
+            None => continue,
+        };
+        if seen.contains(&a) {
             return Some((a, b))
         }
+        seen.insert(b);
     }
     None
 }
 
-fn find_sum_3(numbers: &[String], desired: i32) -> Option<(i32, i32, i32)> {
-    for win in numbers.windows(3) {
-        let a: i32 = win[0].parse().unwrap();
-        let b: i32 = win[1].parse().unwrap();
-        let c: i32 = win[2].parse().unwrap();
-        if a + b + c == desired {
-            return Some((a, b, c))
+/// Find three entries that add up to `desired`, smallest first.
+fn find_sum_3(numbers: &[i64], desired: i64) -> Option<(i64, i64, i64)> {
+    let mut sorted = numbers.to_vec();
+    sorted.sort_unstable();
+
+    for (i, &a) in sorted.iter().enumerate() {
+        // Close in on the other two from either end of the rest
+        let mut low = i + 1;
+        let mut high = sorted.len().saturating_sub(1);
+        while low < high {
+            // Widen so large entries can't overflow the sum
+            let sum = a as i128 + sorted[low] as i128 + sorted[high] as i128;
+            if sum == desired as i128 {
+                return Some((a, sorted[low], sorted[high]))
+            } else if sum < desired as i128 {
+                low += 1;
+            } else {
+                high -= 1;
+            }
         }
     }
     None
 }
 
-pub fn run(config: advent::Config) -> Result<(), Box<dyn Error>> {
-    let mut lines = advent::lines_from_file(config.filename)?;
+/// Find `k` entries that add up to `target`, smallest first.
+fn find_k_sum(numbers: &[i64], k: usize, target: i64) -> Option<Vec<i64>> {
+    if k > numbers.len() {
+        return None;
+    }
+
+    match k {
+        0 => if target == 0 { Some(vec![]) } else { None },
+        2 => find_sum_2(numbers, target).map(|(a, b)| vec![a.min(b), a.max(b)]),
+        3 => find_sum_3(numbers, target).map(|(a, b, c)| vec![a, b, c]),
+        _ => {
+            let mut sorted = numbers.to_vec();
+            sorted.sort_unstable();
+            let windows = sum_windows(&sorted, k, target as i128);
+            // The target is out of reach of any k entries
+            if windows.iter().any(|(low, high)| low > high) {
+                return None;
+            }
+
+            let words: u128 = windows.iter().map(|(low, high)| (high - low) as u128 / 64 + 1).sum();
+            let affordable = words * numbers.len() as u128 <= MAX_SUBSET_SUM_WORK
+                && words * 16 <= MAX_SUBSET_SUM_BYTES as u128;
+            let mut found = if affordable {
+                subset_sum(numbers, &windows, target as i128)
+            } else {
+                meet_in_middle(numbers, k, target as i128)
+            }?;
+            found.sort_unstable();
+            Some(found)
+        }
+    }
+}
+
+/// The range of sums `count` entries can have while leaving room for the
+/// rest of `k` entries to make up `target`, for each count up to `k`. A range
+/// with its low end above its high end can't be reached.
+fn sum_windows(sorted: &[i64], k: usize, target: i128) -> Vec<(i128, i128)> {
+    let smallest = |count: usize| sorted[..count].iter().map(|&n| n as i128).sum::<i128>();
+    let largest = |count: usize| sorted[sorted.len() - count..].iter().map(|&n| n as i128).sum::<i128>();
+    (0..=k)
+        .map(|count| {
+            let low = smallest(count).max(target - largest(k - count));
+            let high = largest(count).min(target - smallest(k - count));
+            (low, high)
+        })
+        .collect()
+}
+
+/// Which sums in a range are reachable, one bit per sum from `low`.
+#[derive(Clone)]
+struct Sums {
+    low: i128,
+    high: i128,
+    bits: Vec<u64>,
+}
+
+impl Sums {
+    fn new(low: i128, high: i128) -> Sums {
+        Sums { low, high, bits: vec![0; (high - low) as usize / 64 + 1] }
+    }
+
+    fn contains(&self, sum: i128) -> bool {
+        if sum < self.low || sum > self.high {
+            return false;
+        }
+        let bit = (sum - self.low) as usize;
+        self.bits[bit / 64] >> (bit % 64) & 1 == 1
+    }
+
+    fn insert(&mut self, sum: i128) {
+        if sum >= self.low && sum <= self.high {
+            let bit = (sum - self.low) as usize;
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Add each sum in `fewer` plus `n`.
+    fn add_shifted(&mut self, fewer: &Sums, n: i64) {
+        // Bit b of `fewer` is the sum fewer.low + b, which is bit b + shift here
+        let shift = fewer.low + n as i128 - self.low;
+        let (words, offset) = (shift.div_euclid(64), shift.rem_euclid(64) as u32);
+        let len = self.bits.len() as i128;
+        for (index, &bits) in fewer.bits.iter().enumerate().filter(|(_, bits)| **bits != 0) {
+            let to = index as i128 + words;
+            if to >= 0 && to < len {
+                self.bits[to as usize] |= bits << offset;
+            }
+            if offset > 0 && to + 1 >= 0 && to + 1 < len {
+                self.bits[(to + 1) as usize] |= bits >> (64 - offset);
+            }
+        }
+        // Keep the bits past the end clear
+        let used = ((self.high - self.low) % 64 + 1) as u32;
+        if used < 64 {
+            *self.bits.last_mut().unwrap() &= (1 << used) - 1;
+        }
+    }
+}
+
+/// Extend the sums for each count of entries with one more entry. Counts are
+/// worked downwards so the entry only extends sums made without it.
+fn add_entry(sums: &mut [Sums], n: i64) {
+    for count in (1..sums.len()).rev() {
+        let (fewer, more) = sums.split_at_mut(count);
+        more[0].add_shifted(&fewer[count - 1], n);
+    }
+}
+
+/// Subset sum over which sums each count of entries can reach, keeping only
+/// the sums in `windows` that could still make up `target`.
+fn subset_sum(numbers: &[i64], windows: &[(i128, i128)], target: i128) -> Option<Vec<i64>> {
+    let k = windows.len() - 1;
+    let mut sums: Vec<Sums> = windows.iter().map(|&(low, high)| Sums::new(low, high)).collect();
+    sums[0].insert(0);
+
+    // Keep a copy of the sums every `block` entries, as many as fit, to work
+    // back from once the target is reached
+    let copy_bytes: usize = sums.iter().map(|s| s.bits.len() * 8).sum();
+    let copies = (MAX_SUBSET_SUM_BYTES / copy_bytes).saturating_sub(2).max(1);
+    let block = numbers.len().max(1).div_ceil(copies);
+    let mut checkpoints = vec![sums.clone()];
+    for (index, &n) in numbers.iter().enumerate() {
+        add_entry(&mut sums, n);
+        if (index + 1) % block == 0 {
+            checkpoints.push(sums.clone());
+        }
+    }
+    if !sums[k].contains(target) {
+        return None;
+    }
+    checkpoints.push(sums);
+
+    // The first entry that makes a sum reachable is part of it, and the rest
+    // of the sum is reachable with the entries before it
+    let mut found = vec![];
+    let mut sum = target;
+    for count in (1..=k).rev() {
+        let after = checkpoints.iter().position(|sums| sums[count].contains(sum)).expect("the target was reached");
+        let mut replay = checkpoints[after - 1].clone();
+        let mut index = (after - 1) * block;
+        loop {
+            add_entry(&mut replay, numbers[index]);
+            if replay[count].contains(sum) {
+                break;
+            }
+            index += 1;
+        }
+        found.push(numbers[index]);
+        sum -= numbers[index] as i128;
+    }
+    Some(found)
+}
+
+/// How many ways there are to choose `r` of `n` things, saturating.
+fn choose(n: usize, r: usize) -> u128 {
+    (0..r as u128).fold(1, |ways: u128, i| ways.saturating_mul(n as u128 - i) / (i + 1))
+}
+
+/// Meet in the middle for entries too spread out for the subset sum. Each
+/// combination of `half` sorted entries is hashed by its sum, then the other
+/// `k - half` are searched for, each combination looking up the rest of the
+/// target among the hashed ones that start after it.
+fn meet_in_middle(numbers: &[i64], k: usize, target: i128) -> Option<Vec<i64>> {
+    let mut sorted = numbers.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len();
+
+    // prefix[i] is the sum of the first i entries
+    let mut prefix = vec![0i128; n + 1];
+    for (i, &entry) in sorted.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + entry as i128;
+    }
+    // Nothing to search for if the target is out of reach of any k entries
+    if target < prefix[k] || target > prefix[n] - prefix[n - k] {
+        return None;
+    }
+
+    // Hash half the entries, or fewer if that would be too many combinations
+    let mut half = (k / 2).max(1);
+    while half > 1 && choose(n, half) > MAX_HASHED_COMBINATIONS as u128 {
+        half -= 1;
+    }
+
+    // For each sum, the combination starting furthest along, as that leaves
+    // the most room for the other half before it
+    let mut later: HashMap<i128, Vec<usize>> = HashMap::new();
+    let mut chosen = vec![];
+    for first in (0..=n - half).rev() {
+        chosen.push(first);
+        hash_combinations(&sorted, half - 1, sorted[first] as i128, &mut chosen, &mut later);
+        chosen.pop();
+    }
+
+    let search = Search { sorted: &sorted, prefix: &prefix, later: &later, half, target };
+    let indices = search.find(0, k - half, 0, &mut chosen)?;
+    Some(indices.into_iter().map(|i| sorted[i]).collect())
+}
+
+/// Add every combination extending `chosen` by `count` later entries to
+/// `later`, keeping the first one seen for each sum.
+fn hash_combinations(
+    sorted: &[i64], count: usize, sum: i128, chosen: &mut Vec<usize>, later: &mut HashMap<i128, Vec<usize>>,
+) {
+    if count == 0 {
+        later.entry(sum).or_insert_with(|| chosen.clone());
+        return;
+    }
+    let start = chosen.last().map_or(0, |&last| last + 1);
+    for i in start..=sorted.len() - count {
+        chosen.push(i);
+        hash_combinations(sorted, count - 1, sum + sorted[i] as i128, chosen, later);
+        chosen.pop();
+    }
+}
+
+/// The search over the half of a meet in the middle that isn't hashed.
+struct Search<'a> {
+    sorted: &'a [i64],
+    prefix: &'a [i128],
+    later: &'a HashMap<i128, Vec<usize>>,
+    half: usize,
+    target: i128,
+}
+
+impl Search<'_> {
+    /// Extend `chosen`, which adds up to `sum`, by `count` entries from
+    /// `start` on, then look up the hashed half for the rest.
+    fn find(&self, start: usize, count: usize, sum: i128, chosen: &mut Vec<usize>) -> Option<Vec<usize>> {
+        if count == 0 {
+            let rest = self.later.get(&(self.target - sum))?;
+            if chosen.last().is_some_and(|&last| rest[0] <= last) {
+                return None;
+            }
+            return Some(chosen.iter().chain(rest).copied().collect());
+        }
+
+        let n = self.sorted.len();
+        let remaining = count + self.half;
+        // Even the largest entries can't make up the rest
+        if sum + self.prefix[n] - self.prefix[n - remaining] < self.target {
+            return None;
+        }
+        for i in start..=n - remaining {
+            // The smallest entries from here on already overshoot
+            if sum + self.prefix[i + remaining] - self.prefix[i] > self.target {
+                break;
+            }
+            // The same value again would only find the same combinations
+            if i > start && self.sorted[i - 1] == self.sorted[i] {
+                continue;
+            }
+            chosen.push(i);
+            let found = self.find(i + 1, count - 1, sum + self.sorted[i] as i128, chosen);
+            chosen.pop();
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+}
+
+/// The fewest entries that add up to `target`, or `None` if no set of them
+/// does. Sums are tracked in a table up to the target, so neither the entries
+/// nor the target can be negative.
+fn fewest_entries(numbers: &[i64], target: i64) -> Result<Option<usize>, AdventError> {
+    if target < 0 || numbers.iter().any(|&n| n < 0) {
+        return Err(AdventError::InvalidInput(
+            "finding the fewest entries needs a target and entries that aren't negative".to_string()
+        ));
+    }
+    // No set of entries can add up to more than all of them
+    let total: u128 = numbers.iter().map(|&n| n as u128).sum();
+    if target as u128 > total {
+        return Ok(None);
+    }
+    if target as u64 >= MAX_TABLE_SIZE as u64 {
+        return Err(AdventError::InvalidInput(format!("a target of {} is too large to find the fewest entries for", target)));
+    }
+
+    let target = target as usize;
+    // fewest[sum] is the fewest entries seen so far that add up to sum
+    let mut fewest: Vec<Option<usize>> = vec![None; target + 1];
+    fewest[0] = Some(0);
+    // The largest sum the entries so far can reach
+    let mut reached = 0;
+    for &n in numbers {
+        let n = n as usize;
+        if n > target {
+            continue;
+        }
+        reached = (reached + n).min(target);
+        // Work downwards so each entry is only used once
+        for sum in (n..=reached).rev() {
+            if let Some(count) = fewest[sum - n] {
+                if fewest[sum].is_none_or(|c| count + 1 < c) {
+                    fewest[sum] = Some(count + 1);
+                }
+            }
+        }
+    }
+
+    Ok(fewest[target])
+}
+
+/// Every distinct set of `k` entries that adds up to `target`, each smallest
+/// first. A value can only be used as many times as it appears.
+fn find_all_k_sums(numbers: &[i64], k: usize, target: i64) -> Vec<Vec<i64>> {
+    let mut sorted = numbers.to_vec();
+    sorted.sort_unstable();
+
+    let mut found = vec![];
+    collect_sorted(&sorted, k, target as i128, &mut vec![], &mut found);
+    found
+}
+
+fn collect_sorted(sorted: &[i64], k: usize, target: i128, chosen: &mut Vec<i64>, found: &mut Vec<Vec<i64>>) {
+    if k == 0 {
+        if target == 0 {
+            found.push(chosen.clone());
+        }
+        return;
+    }
+
+    for (i, &n) in sorted.iter().enumerate() {
+        if sorted.len() - i < k {
+            break;
+        }
+        // Picking the same value again here would repeat combinations
+        if i > 0 && sorted[i - 1] == n {
+            continue;
+        }
+        chosen.push(n);
+        collect_sorted(&sorted[i + 1..], k - 1, target - n as i128, chosen, found);
+        chosen.pop();
+    }
+}
+
+/// The product of the entries, or `None` if it's too large for an i64.
+fn product(entries: &[i64]) -> Option<i64> {
+    entries.iter().try_fold(1i64, |total, &n| total.checked_mul(n))
+}
+
+fn join_entries(entries: &[i64], separator: &str) -> String {
+    let entries: Vec<String> = entries.iter().map(|n| n.to_string()).collect();
+    entries.join(separator)
+}
+
+/// How to show every combination when asked to list them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Listing {
+    Table,
+    Json,
+}
+
+/// A combination and its product.
+type Product<'a> = (&'a Vec<i64>, i64);
+
+/// The combinations with the smallest and largest products, the first found
+/// winning a tie. Combinations whose product overflows are left out.
+fn product_extremes(combinations: &[Vec<i64>]) -> Option<(Product<'_>, Product<'_>)> {
+    let products: Vec<Product> = combinations.iter()
+        .filter_map(|c| product(c).map(|p| (c, p)))
+        .collect();
+    let min = *products.iter().min_by_key(|(_, p)| *p)?;
+    let max = *products.iter().rev().max_by_key(|(_, p)| *p)?;
+    Some((min, max))
+}
+
+fn product_text(entries: &[i64]) -> String {
+    product(entries).map_or("overflow".to_string(), |p| p.to_string())
+}
+
+fn combinations_table(k: usize, target: i64, combinations: &[Vec<i64>]) -> String {
+    let rows: Vec<Vec<String>> = combinations.iter()
+        .map(|c| vec![join_entries(c, " + "), product_text(c)])
+        .collect();
+
+    let noun = if combinations.len() == 1 { "combination" } else { "combinations" };
+    let mut out = format!("{} {} of {} entries add up to {}\n", combinations.len(), noun, k, target);
+    if let Some(((min, min_product), (max, max_product))) = product_extremes(combinations) {
+        out.push_str(&format!("Smallest product: {} ({})\n", min_product, join_entries(min, " * ")));
+        out.push_str(&format!("Largest product: {} ({})\n", max_product, join_entries(max, " * ")));
+    }
+    if !rows.is_empty() {
+        out.push('\n');
+        out.push_str(&advent::table::render(&["Entries", "Product"], &rows));
+    }
+    out
+}
+
+fn combinations_json(k: usize, target: i64, combinations: &[Vec<i64>]) -> String {
+    let entries_json = |c: &[i64]| format!("[{}]", join_entries(c, ","));
+    let extreme_json = |c: &[i64], p: i64| format!("{{\"entries\":{},\"product\":{}}}", entries_json(c), p);
+
+    let (min, max) = match product_extremes(combinations) {
+        Some(((min, min_product), (max, max_product))) => (extreme_json(min, min_product), extreme_json(max, max_product)),
+        None => ("null".to_string(), "null".to_string()),
+    };
+    let all: Vec<String> = combinations.iter().map(|c| entries_json(c)).collect();
+    format!(
+        "{{\"k\":{},\"target\":{},\"count\":{},\"min_product\":{},\"max_product\":{},\"combinations\":[{}]}}",
+        k, target, combinations.len(), min, max, all.join(",")
+    )
+}
+
+pub struct Day1 {
+    k: usize,
+    target: i64,
+    listing: Option<Listing>,
+    fewest: bool,
+}
+
+impl Default for Day1 {
+    fn default() -> Day1 {
+        Day1 { k: 2, target: DESIRED, listing: None, fewest: false }
+    }
+}
+
+impl Day1 {
+    fn fewest(&self, input: &[i64]) -> Result<usize, AdventError> {
+        fewest_entries(input, self.target)?.ok_or_else(|| {
+            AdventError::NoSolution(format!("no set of entries adds up to {}", self.target))
+        })
+    }
+
+    /// Part 2 looks for one more entry than part 1.
+    fn part2_k(&self) -> Result<usize, AdventError> {
+        self.k.checked_add(1).ok_or_else(|| AdventError::InvalidInput(format!("--k {} is too large", self.k)))
+    }
+
+    fn solve(&self, input: &[i64], k: usize) -> Result<Answer, AdventError> {
+        // The combinations themselves are listed by `extra`
+        if self.listing.is_some() {
+            return Ok(find_all_k_sums(input, k, self.target).len().into());
+        }
+
+        match find_k_sum(input, k, self.target) {
+            Some(entries) => {
+                advent::progress!("Found {} numbers! {} = {}", k, join_entries(&entries, " + "), self.target);
+                product(&entries).map(Answer::from).ok_or_else(|| {
+                    AdventError::Overflow(format!("the product {} is too large", join_entries(&entries, " * ")))
+                })
+            }
+            None => Err(AdventError::NoSolution(format!("no {} entries add up to {}", k, self.target))),
+        }
+    }
+}
+
+impl Solution for Day1 {
+    type Input = Vec<i64>;
+
+    const DAY: u32 = 1;
+    const NAME: &'static str = "Report Repair";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        advent::input::parse_lines(input)
+    }
 
-    let desired = 2020;
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        if self.fewest {
+            let count = self.fewest(input)?;
+            advent::progress!("The fewest entries adding up to {} is {}", self.target, count);
+            return Ok(count.into());
+        }
+        self.solve(input, self.k)
+    }
 
-    let mut rng = thread_rng();
-    let mut done = false;
-    while !done {
-        let result = find_sum_2(&lines, desired);
-        if let Some(i) = result {
-            println!("Found 2 numbers! {} + {} = {}", i.0, i.1, desired);
-            println!("Answer = {}", i.0 * i.1);
-            done = true;
+    fn part2(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        if self.fewest {
+            return self.solve(input, self.fewest(input)?);
         }
-        &lines.shuffle(&mut rng);
+        self.solve(input, self.part2_k()?)
     }
-    
-    let mut done = false;
-    while !done {
-        let result = find_sum_3(&lines, desired);
-        if let Some(i) = result {
-            println!("Found 3 numbers! {} + {} + {} = {}", i.0, i.1, i.2, desired);
-            println!("Answer = {}", i.0 * i.1 * i.2);
-            done = true;
+
+    const OPTIONS: &'static [DayOption] = &[
+        DayOption::value("k", "<N>", "find N entries in part 1 and N + 1 in part 2 (default 2)"),
+        DayOption::value("target", "<N>", "the sum the entries must add up to (default 2020)"),
+        DayOption::value("combinations", "<FMT>", "list every combination that adds up, as a table\n\
+            or json, and answer with how many there are"),
+        DayOption::switch("fewest", "find the fewest entries that add up to the target.\n\
+            Part 1 is how many, part 2 the product of one such set,\n\
+            or with --combinations every set of that size"),
+    ];
+
+    fn configure(&mut self, options: &Options) -> Result<(), String> {
+        if let Some(k) = options.parse("k")? {
+            self.k = k;
+        }
+        if let Some(target) = options.parse("target")? {
+            self.target = target;
         }
-        &lines.shuffle(&mut rng);
+        self.listing = match options.get("combinations") {
+            Some("table") => Some(Listing::Table),
+            Some("json") => Some(Listing::Json),
+            Some(other) => return Err(format!("Invalid --combinations: {} (expected table or json)", other)),
+            None => None,
+        };
+        self.fewest = options.contains("fewest");
+        if self.fewest && options.contains("k") {
+            return Err("--fewest finds how many entries are needed, --k is not allowed".to_string());
+        }
+        Ok(())
     }
 
-    Ok(())
+    fn extra(&self, input: &Self::Input, part: Option<u8>) -> Result<Option<String>, AdventError> {
+        let listing = match self.listing {
+            Some(listing) => listing,
+            None => return Ok(None),
+        };
+
+        // Part 1 answers with a count of entries when finding the fewest
+        let mut sizes = vec![];
+        if part.unwrap_or(1) == 1 && !self.fewest {
+            sizes.push(self.k);
+        }
+        if part.unwrap_or(2) == 2 {
+            sizes.push(if self.fewest { self.fewest(input)? } else { self.part2_k()? });
+        }
+
+        let listings: Vec<String> = sizes.into_iter().map(|k| {
+            let combinations = find_all_k_sums(input, k, self.target);
+            match listing {
+                Listing::Table => combinations_table(k, self.target, &combinations),
+                Listing::Json => combinations_json(k, self.target, &combinations),
+            }
+        }).collect();
+        Ok(Some(listings.join("\n")))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options;
 
-    fn generate_vector() -> Vec<String> {
-        return vec![
-            "1".to_string(),
-            "2".to_string(),
-            "3".to_string(),
-            "4".to_string(),
-        ];
+    fn generate_vector() -> Vec<i64> {
+        vec![1, 2, 3, 4]
+    }
+
+    #[test]
+    fn test_parse_when_not_a_number_will_return_err() {
+        let err = Day1::default().parse("1721\n97x\n").unwrap_err();
+        assert!(matches!(err, AdventError::Parse { line: 2, .. }));
     }
 
     #[test]
@@ -92,4 +623,277 @@ mod tests {
         let data = generate_vector();
         assert_eq!(find_sum_3(&data[..], 11), None);
     }
+
+    #[test]
+    fn test_find_sum_2_when_far_apart_will_return_tuple() {
+        assert_eq!(find_sum_2(&[1721, 979, 366, 299, 675, 1456], 2020), Some((1721, 299)));
+    }
+
+    #[test]
+    fn test_find_sum_2_when_half_will_need_two_entries() {
+        assert_eq!(find_sum_2(&[1010, 5], 2020), None);
+        assert_eq!(find_sum_2(&[1010, 5, 1010], 2020), Some((1010, 1010)));
+    }
+
+    #[test]
+    fn test_find_sum_3_when_far_apart_will_return_tuple() {
+        assert_eq!(find_sum_3(&[1721, 979, 366, 299, 675, 1456], 2020), Some((366, 675, 979)));
+    }
+
+    #[test]
+    fn test_find_sum_3_when_too_few_entries_will_return_none() {
+        assert_eq!(find_sum_3(&[], 0), None);
+        assert_eq!(find_sum_3(&[1010, 1010], 2020), None);
+    }
+
+    #[test]
+    fn test_part1_when_no_pair_will_return_no_solution() {
+        let err = Day1::default().part1(&vec![1, 2, 3]).unwrap_err();
+        assert!(matches!(err, AdventError::NoSolution(_)));
+    }
+
+    #[test]
+    fn test_part2_when_k_is_largest_will_return_err() {
+        let day = Day1 { k: usize::MAX, ..Day1::default() };
+        let err = day.part2(&vec![1, 2, 3]).unwrap_err();
+        assert!(matches!(err, AdventError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_find_k_sum_will_return_sorted_entries() {
+        let data = vec![1721, 979, 366, 299, 675, 1456];
+        assert_eq!(find_k_sum(&data, 2, 2020), Some(vec![299, 1721]));
+        assert_eq!(find_k_sum(&data, 3, 2020), Some(vec![366, 675, 979]));
+        assert_eq!(find_k_sum(&data, 4, 2020 + 299), Some(vec![299, 366, 675, 979]));
+        assert_eq!(find_k_sum(&data, 1, 366), Some(vec![366]));
+        assert_eq!(find_k_sum(&data, 0, 0), Some(vec![]));
+    }
+
+    #[test]
+    fn test_find_k_sum_when_not_found_will_return_none() {
+        let data = generate_vector();
+        assert_eq!(find_k_sum(&data, 4, 11), None);
+        assert_eq!(find_k_sum(&data, 5, 10), None);
+        assert_eq!(find_k_sum(&data, 0, 1), None);
+    }
+
+    #[test]
+    fn test_find_k_sum_when_duplicates_will_use_each_entry_once() {
+        assert_eq!(find_k_sum(&[5, 5, 5], 4, 20), None);
+        assert_eq!(find_k_sum(&[5, 5, 5, 5], 4, 20), Some(vec![5, 5, 5, 5]));
+    }
+
+    #[test]
+    fn test_find_k_sum_when_negative_will_search_combinations() {
+        assert_eq!(find_k_sum(&[-3, 7, 1, -1, 4, 2], 4, 5), Some(vec![-3, -1, 2, 7]));
+        assert_eq!(find_k_sum(&[-3, 7, 1, -1, 4, 2], 4, 3), None);
+    }
+
+    #[test]
+    fn test_find_k_sum_when_large_will_be_fast() {
+        // Six entries from a thousand
+        let mut data: Vec<i64> = (0..994).map(|n| 1000 + n).collect();
+        data.extend(&[1, 2, 3, 4, 5, 2005]);
+        let found = find_k_sum(&data, 6, 2020).unwrap();
+        assert_eq!(found.len(), 6);
+        assert_eq!(found.iter().sum::<i64>(), 2020);
+        assert_eq!(find_k_sum(&data, 6, 3), None);
+    }
+
+    #[test]
+    fn test_find_k_sum_when_target_large_will_find_entries() {
+        // Too large a target for a table of every sum up to it
+        let data: Vec<i64> = (0..1000).map(|n| 1_000_000 + n * 499).collect();
+        let target = data[3] + data[250] + data[500] + data[750] + data[999];
+        let found = find_k_sum(&data, 5, target).unwrap();
+        assert_eq!(found.len(), 5);
+        assert_eq!(found.iter().sum::<i64>(), target);
+        assert_eq!(find_k_sum(&data, 5, 99_999_999), None);
+        assert_eq!(find_k_sum(&data, 4, data[0] + data[1] + data[2] + data[3] + 1), None);
+    }
+
+    #[test]
+    fn test_find_k_sum_when_entries_spread_out_will_meet_in_the_middle() {
+        // Far too spread out to track every sum in between
+        let data: Vec<i64> = (1..80).map(|n| n * 1_000_000_007 + n * n * 13).collect();
+        let target = data[0] + data[20] + data[41] + data[60] + data[78];
+        let found = find_k_sum(&data, 5, target).unwrap();
+        assert_eq!(found.iter().sum::<i64>(), target);
+        assert!(found.iter().all(|n| data.contains(n)) && found.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", found);
+        assert_eq!(find_k_sum(&data, 5, target + 1), None);
+    }
+
+    #[test]
+    fn test_find_k_sum_when_six_of_a_thousand_will_find_them() {
+        let data: Vec<i64> = (0..1000).map(|n| 1_000_000 + n * 199).collect();
+        let target = data[1] + data[100] + data[400] + data[600] + data[800] + data[998];
+        let found = find_k_sum(&data, 6, target).unwrap();
+        assert_eq!(found.len(), 6);
+        assert_eq!(found.iter().sum::<i64>(), target);
+        // Every sum of six is 199 apart from the next
+        assert_eq!(find_k_sum(&data, 6, target + 100), None);
+        // Out of reach of any six entries
+        assert_eq!(find_k_sum(&data, 6, 1_999_999), None);
+    }
+
+    #[test]
+    fn test_find_all_k_sums_will_return_each_combination_once() {
+        let data = vec![5, 10, 5, 15, 0, 20];
+        assert_eq!(find_all_k_sums(&data, 2, 20), vec![vec![0, 20], vec![5, 15]]);
+        assert_eq!(find_all_k_sums(&data, 2, 10), vec![vec![0, 10], vec![5, 5]]);
+        assert_eq!(find_all_k_sums(&data, 3, 10), vec![vec![0, 5, 5]]);
+        assert_eq!(find_all_k_sums(&[5, 10], 2, 10), Vec::<Vec<i64>>::new());
+    }
+
+    #[test]
+    fn test_find_all_k_sums_when_negative_will_return_each_combination() {
+        let data = vec![-3, 7, 1, -1, 4, 2];
+        assert_eq!(find_all_k_sums(&data, 3, 5), vec![vec![-3, 1, 7], vec![-1, 2, 4]]);
+    }
+
+    #[test]
+    fn test_combinations_json_will_include_extremes() {
+        let combinations = find_all_k_sums(&[1, 9, 5, 5, 2, 8], 2, 10);
+        assert_eq!(
+            combinations_json(2, 10, &combinations),
+            "{\"k\":2,\"target\":10,\"count\":3,\
+\"min_product\":{\"entries\":[1,9],\"product\":9},\"max_product\":{\"entries\":[5,5],\"product\":25},\
+\"combinations\":[[1,9],[2,8],[5,5]]}"
+        );
+        assert_eq!(
+            combinations_json(2, 3, &[]),
+            "{\"k\":2,\"target\":3,\"count\":0,\"min_product\":null,\"max_product\":null,\"combinations\":[]}"
+        );
+    }
+
+    #[test]
+    fn test_combinations_table_will_list_combinations() {
+        let combinations = find_all_k_sums(&[1, 9, 5, 5, 2, 8], 2, 10);
+        let expected = "\
+3 combinations of 2 entries add up to 10
+Smallest product: 9 (1 * 9)
+Largest product: 25 (5 * 5)
+
+Entries | Product
+--------+--------
+1 + 9   |       9
+2 + 8   |      16
+5 + 5   |      25
+";
+        assert_eq!(combinations_table(2, 10, &combinations), expected);
+    }
+
+    #[test]
+    fn test_configure_when_combinations_will_answer_with_count() {
+        let mut day = Day1::default();
+        day.configure(&options(&[("combinations", "json")])).unwrap();
+
+        let report = day.parse("1010\n1010\n1000\n1020\n1\n").unwrap();
+        assert_eq!(day.part1(&report).unwrap(), Answer::Number(2));
+
+        let extra = day.extra(&report, Some(1)).unwrap().unwrap();
+        assert_eq!(extra.lines().count(), 1);
+        assert!(extra.starts_with("{\"k\":2,\"target\":2020,"), "{}", extra);
+        assert_eq!(day.extra(&report, None).unwrap().unwrap().lines().count(), 2);
+
+        assert!(day.configure(&options(&[("combinations", "json"), ("combinations", "xml")])).is_err());
+    }
+
+    #[test]
+    fn test_parse_when_larger_than_i32_will_return_numbers() {
+        let report = Day1::default().parse("3000000000\n-3000000000\n").unwrap();
+        assert_eq!(report, vec![3_000_000_000, -3_000_000_000]);
+    }
+
+    #[test]
+    fn test_find_sum_when_entries_are_extreme_will_not_overflow() {
+        let data = vec![i64::MAX, i64::MIN, 1, i64::MAX - 1];
+        assert_eq!(find_sum_2(&data, -1), Some((i64::MAX, i64::MIN)));
+        assert_eq!(find_sum_2(&data, i64::MAX), Some((1, i64::MAX - 1)));
+        assert_eq!(find_sum_3(&data, 0), Some((i64::MIN, 1, i64::MAX)));
+    }
+
+    #[test]
+    fn test_product_when_too_large_will_return_none() {
+        assert_eq!(product(&[3_000_000_000, 2]), Some(6_000_000_000));
+        assert_eq!(product(&[3_000_000_000, 3_000_000_000, 3_000_000_000]), None);
+    }
+
+    #[test]
+    fn test_part1_when_product_overflows_will_return_err() {
+        let day = Day1 { target: 8_000_000_000, ..Day1::default() };
+        let err = day.part1(&vec![4_000_000_000, 4_000_000_000, 5]).unwrap_err();
+        assert!(matches!(err, AdventError::Overflow(_)));
+    }
+
+    #[test]
+    fn test_combinations_table_when_product_overflows_will_show_it() {
+        let combinations = vec![vec![1, 9], vec![5_000_000_000, 5_000_000_000]];
+        let table = combinations_table(2, 10, &combinations);
+        assert!(table.contains("Largest product: 9 (1 * 9)"));
+        assert!(table.contains("| overflow"));
+    }
+
+    #[test]
+    fn test_fewest_entries_will_return_smallest_count() {
+        let data = vec![1, 5, 2, 8, 3, 10, 0];
+        assert_eq!(fewest_entries(&data, 10).unwrap(), Some(1));
+        assert_eq!(fewest_entries(&data, 13).unwrap(), Some(2));
+        assert_eq!(fewest_entries(&data, 19).unwrap(), Some(3));
+        assert_eq!(fewest_entries(&data, 0).unwrap(), Some(0));
+        assert_eq!(fewest_entries(&data, 30).unwrap(), None);
+    }
+
+    #[test]
+    fn test_fewest_entries_will_use_each_entry_once() {
+        assert_eq!(fewest_entries(&[5, 1], 10).unwrap(), None);
+        assert_eq!(fewest_entries(&[5, 1, 5], 10).unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_fewest_entries_when_negative_will_return_err() {
+        assert!(fewest_entries(&[5, -1], 4).is_err());
+        assert!(fewest_entries(&[5, 1], -4).is_err());
+        assert!(fewest_entries(&[20_000_000, 1], 20_000_000).is_err());
+    }
+
+    #[test]
+    fn test_fewest_entries_when_target_above_total_will_return_none() {
+        let data: Vec<i64> = (1..=1000).collect();
+        assert_eq!(fewest_entries(&data, 9_999_999).unwrap(), None);
+        assert_eq!(fewest_entries(&data, i64::MAX).unwrap(), None);
+        assert_eq!(fewest_entries(&data[..100], 5050).unwrap(), Some(100));
+    }
+
+    #[test]
+    fn test_configure_when_fewest_will_answer_with_count_and_product() {
+        let mut day = Day1::default();
+        day.configure(&options(&[("fewest", ""), ("target", "21")])).unwrap();
+
+        let report = day.parse("1\n2\n4\n8\n16\n").unwrap();
+        assert_eq!(day.part1(&report).unwrap(), Answer::Number(3));
+        assert_eq!(day.part2(&report).unwrap(), Answer::Number(64));
+
+        let report = day.parse("1\n2\n").unwrap();
+        assert!(matches!(day.part1(&report), Err(AdventError::NoSolution(_))));
+
+        assert!(day.configure(&options(&[("fewest", ""), ("target", "21"), ("k", "3")])).is_err());
+    }
+
+    #[test]
+    fn test_configure_will_change_k_and_target() {
+        let mut day = Day1::default();
+        day.configure(&options(&[("k", "3"), ("target", "2319")])).unwrap();
+
+        let report = day.parse("1721\n979\n366\n299\n675\n1456\n").unwrap();
+        assert!(matches!(day.part1(&report), Err(AdventError::NoSolution(_))));
+        assert_eq!(day.part2(&report).unwrap(), Answer::Number(299 * 366 * 675 * 979));
+    }
+
+    #[test]
+    fn test_parts_will_solve_example() {
+        let report = Day1::default().parse("1721\n979\n366\n299\n675\n1456\n").unwrap();
+        assert_eq!(Day1::default().part1(&report).unwrap(), Answer::Number(514579));
+        assert_eq!(Day1::default().part2(&report).unwrap(), Answer::Number(241861950));
+    }
 }
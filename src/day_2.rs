@@ -1,91 +1,646 @@
-use std::error::Error;
-use std::convert::TryInto;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
-use advent::Config;
+use unicode_segmentation::UnicodeSegmentation;
 
-struct Positions {
-    pub first: usize,
-    pub second: usize,
+use advent::output::json_string;
+use advent::{AdventError, Answer, DayOption, Options, Solution};
+
+mod rules;
+
+use rules::Rule;
+
+/// What a position in a password counts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Char,
+    /// A user perceived character, such as a letter and its accents.
+    Grapheme,
+}
+
+impl Unit {
+    fn split(self, password: &str) -> Vec<&str> {
+        match self {
+            Unit::Char => password.char_indices().map(|(i, c)| &password[i..i + c.len_utf8()]).collect(),
+            Unit::Grapheme => password.graphemes(true).collect(),
+        }
+    }
+}
+
+fn is_letter(unit: &str, letter: char) -> bool {
+    let mut chars = unit.chars();
+    chars.next() == Some(letter) && chars.next().is_none()
+}
+
+/// One line of the password database, `<low>-<high> <letter>: <password>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordEntry {
+    low: usize,
+    high: usize,
+    letter: char,
+    password: String,
+}
+
+fn parse_number(field: &str, column: usize) -> Result<usize, AdventError> {
+    // Positions are 1-indexed
+    match field.parse() {
+        Ok(number) if number >= 1 => Ok(number),
+        _ => Err(AdventError::parse(column, format!("expected a number of at least 1, found \"{}\"", field))),
+    }
+}
+
+impl FromStr for PasswordEntry {
+    type Err = AdventError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.split(' ').collect();
+        let (range, letter_field, password) = match fields[..] {
+            [range, letter_field, password] => (range, letter_field, password),
+            _ => return Err(AdventError::parse(1, format!("expected \"<low>-<high> <letter>: <password>\", found \"{}\"", line))),
+        };
+
+        let (low, high) = range.split_once('-').ok_or_else(|| {
+            AdventError::parse(1, format!("expected two numbers separated by '-', found \"{}\"", range))
+        })?;
+        let high_column = low.chars().count() + 2;
+        let low = parse_number(low, 1)?;
+        let high = parse_number(high, high_column)?;
+
+        let letter_column = range.chars().count() + 2;
+        let letter = match letter_field.chars().collect::<Vec<char>>()[..] {
+            [letter, ':'] => letter,
+            _ => return Err(AdventError::parse(letter_column, format!("expected \"<letter>:\", found \"{}\"", letter_field))),
+        };
+
+        Ok(PasswordEntry { low, high, letter, password: password.to_string() })
+    }
+}
+
+impl fmt::Display for PasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{} {}: {}", self.low, self.high, self.letter, self.password)
+    }
+}
+
+fn times(count: usize) -> String {
+    if count == 1 { "once".to_string() } else { format!("{} times", count) }
+}
+
+/// Why a password breaks a policy.
+#[derive(Debug, Clone, PartialEq)]
+enum Violation {
+    Missing,
+    TooFew { count: usize, low: usize },
+    TooMany { count: usize, high: usize },
+    AtNeitherPosition,
+    AtBothPositions,
+    TooShort { length: usize },
+    BrokenRule(String),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Missing => write!(f, "letter is missing"),
+            Violation::TooFew { count, low } => write!(f, "letter appears {}, fewer than {}", times(*count), low),
+            Violation::TooMany { count, high } => write!(f, "letter appears {}, more than {}", times(*count), high),
+            Violation::AtNeitherPosition => write!(f, "letter is at neither position"),
+            Violation::AtBothPositions => write!(f, "letter is at both positions"),
+            Violation::TooShort { length } => write!(f, "password is only {} long", length),
+            Violation::BrokenRule(rule) => write!(f, "breaks rule \"{}\"", rule),
+        }
+    }
+}
+
+/// A rule deciding whether an entry's password is valid.
+trait PasswordPolicy {
+    /// The name used to choose the policy with `--policy`.
+    fn name(&self) -> &'static str;
+
+    /// Why the entry's password breaks the policy, or `None` if it's valid.
+    fn violation(&self, entry: &PasswordEntry) -> Option<Violation>;
+
+    fn is_valid(&self, entry: &PasswordEntry) -> bool {
+        self.violation(entry).is_none()
+    }
+}
+
+/// The letter appears between `low` and `high` times, inclusive.
+struct OccurrenceRange {
+    unit: Unit,
+}
+
+impl PasswordPolicy for OccurrenceRange {
+    fn name(&self) -> &'static str {
+        "occurrence-range"
+    }
+
+    fn violation(&self, entry: &PasswordEntry) -> Option<Violation> {
+        let count = self.unit.split(&entry.password).iter().filter(|u| is_letter(u, entry.letter)).count();
+        if count == 0 && entry.low > 0 {
+            Some(Violation::Missing)
+        } else if count < entry.low {
+            Some(Violation::TooFew { count, low: entry.low })
+        } else if count > entry.high {
+            Some(Violation::TooMany { count, high: entry.high })
+        } else {
+            None
+        }
+    }
 }
 
-fn is_password_valid(line: String) -> bool {
-    // Parse the line into interesting fields
-    let split_vec = line.split(" ").collect::<Vec<&str>>();
+/// The letter is at exactly one of the positions `low` and `high`.
+struct ExactlyOnePosition {
+    unit: Unit,
+}
 
-    let number_str = String::from(split_vec[0]);
-    let number_vec = number_str.split("-").collect::<Vec<&str>>();
-    let positions = Positions {
-        first: number_vec[0].parse().unwrap(),
-        second: number_vec[1].parse().unwrap()
-    };
+impl PasswordPolicy for ExactlyOnePosition {
+    fn name(&self) -> &'static str {
+        "exactly-one-position"
+    }
 
-    let character = String::from(split_vec[1]).remove(0);
-    let data = String::from(split_vec[2]);
+    fn violation(&self, entry: &PasswordEntry) -> Option<Violation> {
+        let units = self.unit.split(&entry.password);
+        // Positions are 1-indexed, and passwords too short for either are
+        // invalid
+        match (units.get(entry.low - 1), units.get(entry.high - 1)) {
+            (Some(first), Some(second)) => match (is_letter(first, entry.letter), is_letter(second, entry.letter)) {
+                (true, true) => Some(Violation::AtBothPositions),
+                (false, false) => Some(Violation::AtNeitherPosition),
+                _ => None,
+            },
+            _ => Some(Violation::TooShort { length: units.len() }),
+        }
+    }
+}
+
+/// Every rule loaded with `--rules` holds.
+struct RulePolicy<'a> {
+    rules: &'a [Rule],
+    unit: Unit,
+}
 
-    // Ignore passwords that are too short
-    // Data is 1-indexed
-    if (data.len() + 1) >= positions.second.try_into().unwrap() {
-        let string_as_bytes = data.as_bytes();
-        let first_unicode: u8 = string_as_bytes[positions.first - 1];
-        let first_char: char = first_unicode as char;
-        let second_unicode: u8 = string_as_bytes[positions.second - 1];
-        let second_char: char = second_unicode as char;
+impl PasswordPolicy for RulePolicy<'_> {
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+
+    fn violation(&self, entry: &PasswordEntry) -> Option<Violation> {
+        self.rules.iter()
+            .find(|rule| !rule.allows(entry, self.unit))
+            .map(|rule| Violation::BrokenRule(rule.to_string()))
+    }
+}
+
+fn count_valid(entries: &[PasswordEntry], policy: &dyn PasswordPolicy) -> usize {
+    let valid_password_count = entries.iter().filter(|entry| policy.is_valid(entry)).count();
+    advent::progress!("Found {} valid passwords under the {} policy", valid_password_count, policy.name());
+    valid_password_count
+}
+
+/// An entry that broke a policy, and the line it was on.
+struct Failure<'a> {
+    line: usize,
+    entry: &'a PasswordEntry,
+    violation: Violation,
+}
+
+fn audit<'a>(entries: &'a [PasswordEntry], policy: &dyn PasswordPolicy) -> Vec<Failure<'a>> {
+    entries.iter().enumerate()
+        .filter_map(|(index, entry)| {
+            policy.violation(entry).map(|violation| Failure { line: index + 1, entry, violation })
+        })
+        .collect()
+}
 
-        if (first_char == character) ^ (second_char == character) {
-            return true;
+fn failures_by_letter(failures: &[Failure]) -> BTreeMap<char, usize> {
+    let mut counts = BTreeMap::new();
+    for failure in failures {
+        *counts.entry(failure.entry.letter).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn audit_text(policy: &dyn PasswordPolicy, entries: usize, failures: &[Failure]) -> String {
+    let mut out = format!("{}: {} of {} passwords are invalid\n", policy.name(), failures.len(), entries);
+    if failures.is_empty() {
+        return out;
+    }
+
+    let rows: Vec<Vec<String>> = failures.iter()
+        .map(|f| vec![f.line.to_string(), f.entry.to_string(), f.violation.to_string()])
+        .collect();
+    out.push('\n');
+    out.push_str(&advent::table::render(&["Line", "Entry", "Reason"], &rows));
+
+    let rows: Vec<Vec<String>> = failures_by_letter(failures).iter()
+        .map(|(letter, count)| vec![letter.to_string(), count.to_string()])
+        .collect();
+    out.push('\n');
+    out.push_str(&advent::table::render(&["Letter", "Failures"], &rows));
+    out
+}
+
+fn audit_json(policy: &dyn PasswordPolicy, entries: usize, failures: &[Failure]) -> String {
+    let invalid: Vec<String> = failures.iter().map(|f| format!(
+        "{{\"line\":{},\"entry\":{},\"reason\":{}}}",
+        f.line, json_string(&f.entry.to_string()), json_string(&f.violation.to_string())
+    )).collect();
+    let letters: Vec<String> = failures_by_letter(failures).iter()
+        .map(|(letter, count)| format!("{}:{}", json_string(&letter.to_string()), count))
+        .collect();
+    format!(
+        "{{\"policy\":{},\"entries\":{},\"valid\":{},\"invalid\":[{}],\"failures_by_letter\":{{{}}}}}",
+        json_string(policy.name()), entries, entries - failures.len(), invalid.join(","), letters.join(",")
+    )
+}
+
+/// How to write an audit of the invalid passwords.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Report {
+    Text,
+    Json,
+}
+
+const POLICIES: [&dyn PasswordPolicy; 2] = [
+    &OccurrenceRange { unit: Unit::Char },
+    &ExactlyOnePosition { unit: Unit::Char },
+];
+
+pub struct Day2 {
+    // Only this policy is counted, if given
+    policy: Option<&'static str>,
+    unit: Unit,
+    audit: Option<Report>,
+    // Replaces the puzzle policies, if given
+    rules: Option<Vec<Rule>>,
+}
+
+impl Default for Day2 {
+    fn default() -> Day2 {
+        Day2 { policy: None, unit: Unit::Char, audit: None, rules: None }
+    }
+}
+
+impl Day2 {
+    /// Call `f` with the policy `part` counts passwords under, or return
+    /// `None` if that part isn't counted.
+    fn with_policy<T>(&self, part: u8, f: impl FnOnce(&dyn PasswordPolicy) -> T) -> Option<T> {
+        let occurrence_range = OccurrenceRange { unit: self.unit };
+        let exactly_one_position = ExactlyOnePosition { unit: self.unit };
+        let rule_policy;
+        let policy: &dyn PasswordPolicy = match (part, &self.rules) {
+            (1, Some(rules)) => {
+                rule_policy = RulePolicy { rules, unit: self.unit };
+                &rule_policy
+            }
+            (1, None) => &occurrence_range,
+            // The rules replace both puzzle policies
+            (_, Some(_)) => return None,
+            (_, None) => &exactly_one_position,
+        };
+
+        if matches!(self.policy, Some(name) if name != policy.name()) {
+            return None;
         }
+        Some(f(policy))
     }
 
-    return false;
+    fn solve(&self, input: &[PasswordEntry], part: u8) -> Result<Answer, AdventError> {
+        Ok(self.with_policy(part, |policy| count_valid(input, policy).into()).unwrap_or(Answer::Unsolved))
+    }
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let lines = advent::lines_from_file(config.filename)?;
+impl Solution for Day2 {
+    type Input = Vec<PasswordEntry>;
 
-    let mut valid_password_count: i32 = 0;
+    const DAY: u32 = 2;
+    const NAME: &'static str = "Password Philosophy";
 
-    for line in lines {
-        if is_password_valid(line) {
-            valid_password_count += 1;
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        advent::input::parse_lines_with(input, str::parse)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        self.solve(input, 1)
+    }
+
+    fn part2(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        self.solve(input, 2)
+    }
+
+    const OPTIONS: &'static [DayOption] = &[
+        DayOption::value("policy", "<NAME>", "only count passwords under one policy, either\n\
+            occurrence-range (part 1) or exactly-one-position (part 2)"),
+        DayOption::value("positions", "<UNIT>", "count password positions in chars (default) or\n\
+            graphemes, which keep a letter and its accents together"),
+        DayOption::value("audit", "<FMT>", "list each invalid password with the reason and\n\
+            failures per letter, as text or json"),
+        DayOption::value("rules", "<FILE>", "count passwords in part 1 that pass every rule in\n\
+            FILE, one per line, such as\n\
+            count(letter) in low..=high AND length >= 8"),
+    ];
+
+    fn configure(&mut self, options: &Options) -> Result<(), String> {
+        if let Some(name) = options.get("policy") {
+            let policy = POLICIES.iter().find(|p| p.name() == name).ok_or_else(|| {
+                let names: Vec<&str> = POLICIES.iter().map(|p| p.name()).collect();
+                format!("Invalid --policy: {} (expected {})", name, names.join(" or "))
+            })?;
+            self.policy = Some(policy.name());
         }
+        self.unit = match options.get("positions") {
+            Some("chars") | None => Unit::Char,
+            Some("graphemes") => Unit::Grapheme,
+            Some(other) => return Err(format!("Invalid --positions: {} (expected chars or graphemes)", other)),
+        };
+        self.audit = match options.get("audit") {
+            Some("text") => Some(Report::Text),
+            Some("json") => Some(Report::Json),
+            Some(other) => return Err(format!("Invalid --audit: {} (expected text or json)", other)),
+            None => None,
+        };
+        self.rules = match options.get("rules") {
+            Some(_) if self.policy.is_some() => return Err("--rules can't be used with --policy".to_string()),
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|e| format!("Problem reading {}: {}", path, e))?;
+                Some(rules::parse_rules(&text).map_err(|e| format!("Invalid --rules {}: {}", path, e))?)
+            }
+            None => None,
+        };
+        Ok(())
     }
 
-    println!("Found {} valid passwords", valid_password_count);
+    fn extra(&self, input: &Self::Input, part: Option<u8>) -> Result<Option<String>, AdventError> {
+        let report = match self.audit {
+            Some(report) => report,
+            None => return Ok(None),
+        };
 
-    Ok(())
+        // An audit for each part that counts passwords
+        let audits: Vec<String> = [1, 2].iter()
+            .filter(|&&p| part.unwrap_or(p) == p)
+            .filter_map(|&p| self.with_policy(p, |policy| {
+                let failures = audit(input, policy);
+                match report {
+                    Report::Text => audit_text(policy, input.len(), &failures),
+                    Report::Json => audit_json(policy, input.len(), &failures),
+                }
+            }))
+            .collect();
+        Ok(if audits.is_empty() { None } else { Some(audits.join("\n")) })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options;
+
+    fn is_password_valid(line: &str, policy: &dyn PasswordPolicy) -> bool {
+        policy.is_valid(&line.parse().unwrap())
+    }
 
     #[test]
     fn test_is_password_valid_when_too_short_will_return_false() {
-        let test_line = "1-10 x: xyz".to_string();
-        assert_eq!(is_password_valid(test_line), false);
+        assert!(!is_password_valid("1-10 x: xyz", &ExactlyOnePosition { unit: Unit::Char }));
     }
 
     #[test]
     fn test_is_password_valid_when_no_char_will_return_false() {
-        let test_line = "1-5 x: abcdef".to_string();
-        assert_eq!(is_password_valid(test_line), false);
+        assert!(!is_password_valid("1-5 x: abcdef", &ExactlyOnePosition { unit: Unit::Char }));
     }
 
     #[test]
     fn test_is_password_valid_when_char_appears_twice_will_return_false() {
-        let test_line = "1-5 a: abcdaf".to_string();
-        assert_eq!(is_password_valid(test_line), false);
+        assert!(!is_password_valid("1-5 a: abcdaf", &ExactlyOnePosition { unit: Unit::Char }));
     }
 
     #[test]
     fn test_is_password_valid_when_char_appears_first_will_return_true() {
-        let test_line = "1-5 a: abcdef".to_string();
-        assert_eq!(is_password_valid(test_line), true);
+        assert!(is_password_valid("1-5 a: abcdef", &ExactlyOnePosition { unit: Unit::Char }));
     }
 
     #[test]
     fn test_is_password_valid_when_char_appears_second_will_return_true() {
-        let test_line = "1-5 e: abcdef".to_string();
-        assert_eq!(is_password_valid(test_line), true);
+        assert!(is_password_valid("1-5 e: abcdef", &ExactlyOnePosition { unit: Unit::Char }));
+    }
+
+    #[test]
+    fn test_is_password_valid_when_non_ascii_will_use_char_positions() {
+        let test_data = vec![
+            ("1-3 é: éaé", false),
+            ("1-3 é: éab", true),
+            ("2-3 a: éab", true),
+            ("2-3 b: éab", true),
+            ("1-3 ß: aßc", false),
+            ("3-4 c: aßc", false),
+        ];
+        for (line, expected) in test_data {
+            assert_eq!(is_password_valid(line, &ExactlyOnePosition { unit: Unit::Char }), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_is_password_valid_when_position_is_last_char_will_check_it() {
+        assert!(is_password_valid("1-3 c: abc", &ExactlyOnePosition { unit: Unit::Char }));
+        assert!(!is_password_valid("1-4 c: abc", &ExactlyOnePosition { unit: Unit::Char }));
+        assert!(!is_password_valid("4-5 c: abc", &ExactlyOnePosition { unit: Unit::Char }));
+    }
+
+    #[test]
+    fn test_is_password_valid_when_graphemes_will_keep_accents_together() {
+        // "e" followed by a combining acute accent
+        let line = "1-2 e: e\u{301}x";
+        assert!(is_password_valid(line, &ExactlyOnePosition { unit: Unit::Char }));
+        assert!(!is_password_valid(line, &ExactlyOnePosition { unit: Unit::Grapheme }));
+
+        let line = "1-1 e: e\u{301}e";
+        assert!(!is_password_valid(line, &OccurrenceRange { unit: Unit::Char }));
+        assert!(is_password_valid(line, &OccurrenceRange { unit: Unit::Grapheme }));
+    }
+
+    #[test]
+    fn test_occurrence_range_when_non_ascii_will_count_chars() {
+        assert!(is_password_valid("2-2 ü: üaü", &OccurrenceRange { unit: Unit::Char }));
+        assert!(!is_password_valid("1-1 u: üaü", &OccurrenceRange { unit: Unit::Char }));
+    }
+
+    #[test]
+    fn test_from_str_when_malformed_will_return_err() {
+        let test_data = vec![
+            ("1-5 a abcdef", 5),
+            ("1-5 ab: abcdef", 5),
+            ("1 a: abcdef", 1),
+            ("0-5 a: abcdef", 1),
+            ("1-x a: abcdef", 3),
+            ("1-5 a:", 1),
+        ];
+        for (line, expected_column) in test_data {
+            match line.parse::<PasswordEntry>() {
+                Err(AdventError::Parse { column, .. }) => assert_eq!(column, expected_column, "{}", line),
+                other => panic!("expected a parse error for {}, got {:?}", line, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_str_will_return_entry() {
+        let expected = PasswordEntry { low: 1, high: 13, letter: 'é', password: "café".to_string() };
+        assert_eq!("1-13 é: café".parse::<PasswordEntry>().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_when_malformed_will_name_line_and_token() {
+        let err = Day2::default().parse("1-3 a: abcde\n1-x b: cdefg\n").unwrap_err();
+        assert_eq!(err.to_string(), "Parse error on line 2, column 3: expected a number of at least 1, found \"x\"");
+    }
+
+    #[test]
+    fn test_occurrence_range_will_count_letter() {
+        let test_data = vec![
+            ("1-3 a: abcde", true),
+            ("1-3 b: cdefg", false),
+            ("2-9 c: ccccccccc", true),
+            ("2-3 c: cccc", false),
+        ];
+        for (line, expected) in test_data {
+            assert_eq!(is_password_valid(line, &OccurrenceRange { unit: Unit::Char }), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_parts_will_count_each_policy() {
+        let day = Day2::default();
+        let input = day.parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n").unwrap();
+        assert_eq!(day.part1(&input).unwrap(), Answer::Number(2));
+        assert_eq!(day.part2(&input).unwrap(), Answer::Number(1));
+    }
+
+    #[test]
+    fn test_configure_when_policy_given_will_only_count_it() {
+        let mut day = Day2::default();
+        day.configure(&options(&[("policy", "exactly-one-position")])).unwrap();
+
+        let input = day.parse("1-3 a: abcde\n").unwrap();
+        assert_eq!(day.part1(&input).unwrap(), Answer::Unsolved);
+        assert_eq!(day.part2(&input).unwrap(), Answer::Number(1));
+
+        assert!(day.configure(&options(&[("policy", "exactly-one-position"), ("policy", "strict")])).is_err());
+    }
+
+    fn audit_lines(input: &str, policy: &dyn PasswordPolicy) -> Vec<(usize, String)> {
+        let entries = Day2::default().parse(input).unwrap();
+        audit(&entries, policy).iter().map(|f| (f.line, f.violation.to_string())).collect()
+    }
+
+    #[test]
+    fn test_audit_will_give_occurrence_range_reasons() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-3 c: cxx\n1-2 d: ddd\n";
+        let expected = vec![
+            (2, "letter is missing".to_string()),
+            (3, "letter appears once, fewer than 2".to_string()),
+            (4, "letter appears 3 times, more than 2".to_string()),
+        ];
+        assert_eq!(audit_lines(input, &OccurrenceRange { unit: Unit::Char }), expected);
+    }
+
+    #[test]
+    fn test_audit_will_give_exactly_one_position_reasons() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-9 a: abc\n";
+        let expected = vec![
+            (2, "letter is at neither position".to_string()),
+            (3, "letter is at both positions".to_string()),
+            (4, "password is only 3 long".to_string()),
+        ];
+        assert_eq!(audit_lines(input, &ExactlyOnePosition { unit: Unit::Char }), expected);
+    }
+
+    #[test]
+    fn test_audit_text_will_list_failures_and_letters() {
+        let entries = Day2::default().parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n").unwrap();
+        let policy = ExactlyOnePosition { unit: Unit::Char };
+        let expected = "\
+exactly-one-position: 2 of 3 passwords are invalid
+
+Line | Entry            | Reason
+-----+------------------+------------------------------
+   2 | 1-3 b: cdefg     | letter is at neither position
+   3 | 2-9 c: ccccccccc | letter is at both positions
+
+Letter | Failures
+-------+---------
+b      |        1
+c      |        1
+";
+        assert_eq!(audit_text(&policy, entries.len(), &audit(&entries, &policy)), expected);
+    }
+
+    #[test]
+    fn test_audit_json_will_write_object() {
+        let entries = Day2::default().parse("1-3 a: abcde\n1-1 b: b\"b\n").unwrap();
+        let policy = OccurrenceRange { unit: Unit::Char };
+        assert_eq!(
+            audit_json(&policy, entries.len(), &audit(&entries, &policy)),
+            "{\"policy\":\"occurrence-range\",\"entries\":2,\"valid\":1,\
+\"invalid\":[{\"line\":2,\"entry\":\"1-1 b: b\\\"b\",\"reason\":\"letter appears 2 times, more than 1\"}],\
+\"failures_by_letter\":{\"b\":1}}"
+        );
+    }
+
+    #[test]
+    fn test_extra_when_audit_given_will_audit_each_counted_policy() {
+        let mut day = Day2::default();
+        let input = day.parse("1-3 a: abcde\n1-3 b: cdefg\n").unwrap();
+        assert_eq!(day.extra(&input, None).unwrap(), None);
+
+        day.configure(&options(&[("audit", "json")])).unwrap();
+        let audits = day.extra(&input, None).unwrap().unwrap();
+        let lines: Vec<&str> = audits.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("{\"policy\":\"occurrence-range\","), "{}", lines[0]);
+        assert!(lines[1].starts_with("{\"policy\":\"exactly-one-position\","), "{}", lines[1]);
+
+        day.configure(&options(&[("audit", "json"), ("policy", "occurrence-range")])).unwrap();
+        assert_eq!(day.extra(&input, Some(2)).unwrap(), None);
+        assert_eq!(day.extra(&input, Some(1)).unwrap().unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_part1_when_rules_given_will_count_them() {
+        let rules = rules::parse_rules("# The puzzle's first policy\ncount(letter) in low..=high\nlength >= 6\n").unwrap();
+        let day = Day2 { rules: Some(rules), ..Day2::default() };
+
+        let input = day.parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n").unwrap();
+        assert_eq!(day.part1(&input).unwrap(), Answer::Number(1));
+        assert_eq!(day.part2(&input).unwrap(), Answer::Unsolved);
+
+        let policy = RulePolicy { rules: day.rules.as_ref().unwrap(), unit: Unit::Char };
+        let reasons: Vec<String> = audit(&input, &policy).iter().map(|f| f.violation.to_string()).collect();
+        assert_eq!(reasons, vec!["breaks rule \"length >= 6\"", "breaks rule \"count(letter) in low..=high\""]);
+    }
+
+    #[test]
+    fn test_configure_when_rules_file_missing_will_return_err() {
+        let mut day = Day2::default();
+        let err = day.configure(&options(&[("rules", "no/such/rules.txt")])).unwrap_err();
+        assert!(err.starts_with("Problem reading no/such/rules.txt: "));
+    }
+
+    #[test]
+    fn test_configure_when_rules_and_policy_given_will_return_err() {
+        let mut day = Day2::default();
+        let err = day.configure(&options(&[("policy", "occurrence-range"), ("rules", "rules.txt")])).unwrap_err();
+        assert_eq!(err, "--rules can't be used with --policy");
+    }
+
+    #[test]
+    fn test_configure_when_positions_given_will_set_unit() {
+        let mut day = Day2::default();
+        day.configure(&options(&[("positions", "graphemes")])).unwrap();
+        assert_eq!(day.unit, Unit::Grapheme);
+
+        assert!(day.configure(&options(&[("positions", "graphemes"), ("positions", "bytes")])).is_err());
     }
 }
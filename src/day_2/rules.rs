@@ -0,0 +1,500 @@
+use std::fmt;
+
+use advent::AdventError;
+
+use super::{is_letter, PasswordEntry, Unit};
+
+// Password rules are written as expressions over an entry, for example
+//
+//     count(letter) in low..=high AND (at(low) xor at(high))
+//     length >= 8 AND has(digit) AND NOT has(space)
+//
+// Values are numbers, `low`, `high`, `length` and `count(<class>)`. They are
+// compared with `==`, `!=`, `<`, `<=`, `>`, `>=` or `in a..b` / `in a..=b`.
+// `at(<value>)` checks the letter is at a 1-indexed position and
+// `has(<class>)` that anything in the class appears. Classes are `letter`
+// (the entry's letter), a quoted character such as 'x', `digit`, `upper`,
+// `lower`, `alpha`, `symbol` and `space`. NOT binds tightest, then AND, XOR
+// and OR. Keywords can be upper or lower case.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Class {
+    Letter,
+    Char(char),
+    Digit,
+    Upper,
+    Lower,
+    Alpha,
+    Symbol,
+    Space,
+}
+
+impl Class {
+    fn matches(self, unit: &str, entry: &PasswordEntry) -> bool {
+        let first = match unit.chars().next() {
+            Some(c) => c,
+            None => return false,
+        };
+        match self {
+            Class::Letter => is_letter(unit, entry.letter),
+            Class::Char(c) => is_letter(unit, c),
+            Class::Digit => first.is_numeric(),
+            Class::Upper => first.is_uppercase(),
+            Class::Lower => first.is_lowercase(),
+            Class::Alpha => first.is_alphabetic(),
+            Class::Symbol => !first.is_alphanumeric() && !first.is_whitespace(),
+            Class::Space => first.is_whitespace(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Number(usize),
+    Low,
+    High,
+    Length,
+    Count(Class),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Value, Comparison, Value),
+    InRange { value: Value, start: Value, end: Value, inclusive: bool },
+    At(Value),
+    Has(Class),
+}
+
+/// What an expression is checked against: an entry and its password split
+/// into positions.
+struct Context<'a> {
+    entry: &'a PasswordEntry,
+    units: Vec<&'a str>,
+}
+
+impl<'a> Context<'a> {
+    fn value(&self, value: Value) -> usize {
+        match value {
+            Value::Number(n) => n,
+            Value::Low => self.entry.low,
+            Value::High => self.entry.high,
+            Value::Length => self.units.len(),
+            Value::Count(class) => self.units.iter().filter(|u| class.matches(u, self.entry)).count(),
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Not(inner) => !self.eval(inner),
+            Expr::And(a, b) => self.eval(a) && self.eval(b),
+            Expr::Xor(a, b) => self.eval(a) ^ self.eval(b),
+            Expr::Or(a, b) => self.eval(a) || self.eval(b),
+            Expr::Compare(a, comparison, b) => {
+                let (a, b) = (self.value(*a), self.value(*b));
+                match comparison {
+                    Comparison::Equal => a == b,
+                    Comparison::NotEqual => a != b,
+                    Comparison::Less => a < b,
+                    Comparison::LessOrEqual => a <= b,
+                    Comparison::Greater => a > b,
+                    Comparison::GreaterOrEqual => a >= b,
+                }
+            }
+            Expr::InRange { value, start, end, inclusive } => {
+                let (value, start, end) = (self.value(*value), self.value(*start), self.value(*end));
+                value >= start && (value < end || (*inclusive && value == end))
+            }
+            Expr::At(position) => {
+                // Positions are 1-indexed
+                let position = self.value(*position);
+                position >= 1 && self.units.get(position - 1).is_some_and(|u| is_letter(u, self.entry.letter))
+            }
+            Expr::Has(class) => self.units.iter().any(|u| class.matches(u, self.entry)),
+        }
+    }
+}
+
+/// One rule, as written, and the expression it was parsed into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    text: String,
+    expr: Expr,
+}
+
+impl Rule {
+    pub fn parse(text: &str) -> Result<Rule, AdventError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, position: 0, end_column: text.chars().count() + 1 };
+        let expr = parser.expr()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(AdventError::parse(token.column, format!("unexpected \"{}\"", token.kind)));
+        }
+        Ok(Rule { text: text.trim().to_string(), expr })
+    }
+
+    pub fn allows(&self, entry: &PasswordEntry, unit: Unit) -> bool {
+        let context = Context { entry, units: unit.split(&entry.password) };
+        context.eval(&self.expr)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Parse a file of rules, one per line. Blank lines and lines starting with
+/// `#` are skipped.
+pub fn parse_rules(input: &str) -> Result<Vec<Rule>, AdventError> {
+    let mut rules = vec![];
+    for (index, line) in advent::input::lines(input).enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        rules.push(Rule::parse(line).map_err(|e| e.at_line(index + 1))?);
+    }
+
+    if rules.is_empty() {
+        return Err(AdventError::InvalidInput("there are no rules".to_string()));
+    }
+    Ok(rules)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(usize),
+    Word(String),
+    Char(char),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Number(n) => write!(f, "{}", n),
+            TokenKind::Word(word) => f.write_str(word),
+            TokenKind::Char(c) => write!(f, "'{}'", c),
+            TokenKind::Symbol(symbol) => f.write_str(symbol),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+// Longest first, so "..=" isn't read as ".." followed by "="
+const SYMBOLS: [&str; 10] = ["..=", "..", "==", "!=", "<=", ">=", "<", ">", "(", ")"];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, AdventError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            let number = digits.parse().map_err(|_| AdventError::parse(column, format!("number \"{}\" is too large", digits)))?;
+            tokens.push(Token { kind: TokenKind::Number(number), column });
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token { kind: TokenKind::Word(chars[start..i].iter().collect()), column });
+        } else if c == '\'' {
+            match chars.get(i + 1..i + 3) {
+                Some([quoted, '\'']) => {
+                    tokens.push(Token { kind: TokenKind::Char(*quoted), column });
+                    i += 3;
+                }
+                _ => return Err(AdventError::parse(column, "expected a single quoted character such as 'a'")),
+            }
+        } else {
+            let rest: String = chars[i..].iter().take(3).collect();
+            let symbol = SYMBOLS.iter().find(|s| rest.starts_with(*s))
+                .ok_or_else(|| AdventError::parse(column, format!("unexpected '{}'", c)))?;
+            tokens.push(Token { kind: TokenKind::Symbol(symbol), column });
+            i += symbol.chars().count();
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    // Where to report running out of tokens
+    end_column: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.position).map(|t| &t.kind)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.end_column, |t| t.column)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<TokenKind, AdventError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token.kind.clone())
+            }
+            None => Err(AdventError::parse(self.end_column, format!("expected {}, found the end of the rule", expected))),
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str, found: &TokenKind) -> Result<T, AdventError> {
+        // The token has already been taken, so point back at it
+        let column = self.tokens[self.position - 1].column;
+        Err(AdventError::parse(column, format!("expected {}, found \"{}\"", expected, found)))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(TokenKind::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn symbol(&mut self, symbol: &'static str) -> Result<(), AdventError> {
+        match self.next(&format!("\"{}\"", symbol))? {
+            TokenKind::Symbol(s) if s == symbol => Ok(()),
+            other => self.unexpected(&format!("\"{}\"", symbol), &other),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, AdventError> {
+        let mut expr = self.xor()?;
+        while self.is_keyword("or") {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.xor()?));
+        }
+        Ok(expr)
+    }
+
+    fn xor(&mut self) -> Result<Expr, AdventError> {
+        let mut expr = self.and()?;
+        while self.is_keyword("xor") {
+            self.position += 1;
+            expr = Expr::Xor(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, AdventError> {
+        let mut expr = self.unary()?;
+        while self.is_keyword("and") {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, AdventError> {
+        if self.is_keyword("not") {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == Some(&TokenKind::Symbol("(")) {
+            self.position += 1;
+            let expr = self.expr()?;
+            self.symbol(")")?;
+            return Ok(expr);
+        }
+        if self.is_keyword("at") {
+            self.position += 1;
+            self.symbol("(")?;
+            let position = self.value()?;
+            self.symbol(")")?;
+            return Ok(Expr::At(position));
+        }
+        if self.is_keyword("has") {
+            self.position += 1;
+            return Ok(Expr::Has(self.class_argument()?));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, AdventError> {
+        let value = self.value()?;
+
+        if self.is_keyword("in") {
+            self.position += 1;
+            let start = self.value()?;
+            let inclusive = match self.next("\"..\" or \"..=\"")? {
+                TokenKind::Symbol("..") => false,
+                TokenKind::Symbol("..=") => true,
+                other => return self.unexpected("\"..\" or \"..=\"", &other),
+            };
+            let end = self.value()?;
+            return Ok(Expr::InRange { value, start, end, inclusive });
+        }
+
+        let expected = "a comparison such as \">=\" or \"in\"";
+        let comparison = match self.next(expected)? {
+            TokenKind::Symbol("==") => Comparison::Equal,
+            TokenKind::Symbol("!=") => Comparison::NotEqual,
+            TokenKind::Symbol("<") => Comparison::Less,
+            TokenKind::Symbol("<=") => Comparison::LessOrEqual,
+            TokenKind::Symbol(">") => Comparison::Greater,
+            TokenKind::Symbol(">=") => Comparison::GreaterOrEqual,
+            other => return self.unexpected(expected, &other),
+        };
+        Ok(Expr::Compare(value, comparison, self.value()?))
+    }
+
+    fn value(&mut self) -> Result<Value, AdventError> {
+        let expected = "a number, low, high, length or count(...)";
+        let column = self.column();
+        match self.next(expected)? {
+            TokenKind::Number(n) => Ok(Value::Number(n)),
+            TokenKind::Word(word) => match word.to_lowercase().as_str() {
+                "low" => Ok(Value::Low),
+                "high" => Ok(Value::High),
+                "length" => Ok(Value::Length),
+                "count" => Ok(Value::Count(self.class_argument()?)),
+                _ => Err(AdventError::parse(column, format!("expected {}, found \"{}\"", expected, word))),
+            },
+            other => self.unexpected(expected, &other),
+        }
+    }
+
+    /// A class in brackets, as taken by `count` and `has`.
+    fn class_argument(&mut self) -> Result<Class, AdventError> {
+        self.symbol("(")?;
+        let expected = "letter, a quoted character, digit, upper, lower, alpha, symbol or space";
+        let class = match self.next(expected)? {
+            TokenKind::Char(c) => Class::Char(c),
+            TokenKind::Word(word) => match word.to_lowercase().as_str() {
+                "letter" => Class::Letter,
+                "digit" => Class::Digit,
+                "upper" => Class::Upper,
+                "lower" => Class::Lower,
+                "alpha" => Class::Alpha,
+                "symbol" => Class::Symbol,
+                "space" => Class::Space,
+                _ => return self.unexpected(expected, &TokenKind::Word(word)),
+            },
+            other => return self.unexpected(expected, &other),
+        };
+        self.symbol(")")?;
+        Ok(class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allows(rule: &str, line: &str) -> bool {
+        Rule::parse(rule).unwrap().allows(&line.parse().unwrap(), Unit::Char)
+    }
+
+    #[test]
+    fn test_allows_when_puzzle_rules_will_match_policies() {
+        let occurrence = "count(letter) in low..=high";
+        let position = "at(low) xor at(high)";
+        let test_data = vec![
+            ("1-3 a: abcde", true, true),
+            ("1-3 b: cdefg", false, false),
+            ("2-9 c: ccccccccc", true, false),
+        ];
+        for (line, occurrence_valid, position_valid) in test_data {
+            assert_eq!(allows(occurrence, line), occurrence_valid, "{}", line);
+            assert_eq!(allows(position, line), position_valid, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_allows_will_bind_and_tighter_than_xor() {
+        // (false AND true) xor true
+        assert!(allows("count(letter) == 0 AND at(low) xor at(high)", "1-3 a: abade"));
+        assert!(!allows("count(letter) == 0 AND (at(low) xor at(high))", "1-3 a: abade"));
+    }
+
+    #[test]
+    fn test_allows_when_length_and_classes_will_check_password() {
+        let rule = "length >= 8 AND has(digit) AND has(upper) AND NOT has(space) AND count('!') < 2";
+        assert!(allows(rule, "1-1 a: Passw0rd!"));
+        assert!(!allows(rule, "1-1 a: Passw0rd!!"));
+        assert!(!allows(rule, "1-1 a: Pass0!"));
+        assert!(!allows(rule, "1-1 a: password1"));
+        assert!(allows("count(symbol) == 2 OR has(lower)", "1-1 a: ÉÉ#ü"));
+    }
+
+    #[test]
+    fn test_allows_when_range_is_exclusive_will_skip_end() {
+        assert!(allows("length in 1..4", "1-1 a: abc"));
+        assert!(!allows("length in 1..4", "1-1 a: abcd"));
+        assert!(allows("LENGTH IN 1..=4", "1-1 a: abcd"));
+    }
+
+    #[test]
+    fn test_allows_when_position_out_of_range_will_return_false() {
+        assert!(!allows("at(0)", "1-1 a: abc"));
+        assert!(!allows("at(4)", "1-1 a: abc"));
+        assert!(allows("at(length)", "1-1 c: abc"));
+    }
+
+    #[test]
+    fn test_parse_when_malformed_will_report_column() {
+        let test_data = vec![
+            ("count(letter) in low", 21),
+            ("length >= ", 11),
+            ("at(low) xor", 12),
+            ("has(vowel)", 5),
+            ("count(letter) ~ 1", 15),
+            ("length > 1 1", 12),
+            ("(length > 1", 12),
+            ("width > 1", 1),
+            ("count('ab') > 1", 7),
+        ];
+        for (rule, expected_column) in test_data {
+            match Rule::parse(rule) {
+                Err(AdventError::Parse { column, .. }) => assert_eq!(column, expected_column, "{}", rule),
+                other => panic!("expected a parse error for {}, got {:?}", rule, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_rules_will_skip_comments_and_report_lines() {
+        let rules = parse_rules("# Corporate policy\n\nlength >= 8\r\nhas(digit)\n").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].to_string(), "has(digit)");
+
+        let err = parse_rules("# Corporate policy\nlength >= 8\nhas(digits)\n").unwrap_err();
+        assert!(matches!(err, AdventError::Parse { line: 3, column: 5, .. }));
+
+        assert!(parse_rules("# Nothing here\n").is_err());
+    }
+}
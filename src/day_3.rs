@@ -1,20 +1,26 @@
-use std::io::{self, Write};
-use std::convert::TryInto;
-use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::thread;
 
-use advent::Config;
+use advent::grid::{Grid, Wrap};
+use advent::{AdventError, Answer, DayOption, Options, Solution};
+
+mod render;
+
+use render::Target;
 
 mod position_mod {
     pub struct Position {
-        pub x: usize,
-        pub y: usize,
-        right: usize,
-        down: usize,
+        pub x: isize,
+        pub y: isize,
+        right: isize,
+        down: isize,
     }
 
     impl Position {
-        pub fn new(right: usize, down: usize) -> Position {
-            Position { x: 0, y: 0, right: right, down: down }
+        pub fn new(y: isize, right: isize, down: isize) -> Position {
+            Position { x: 0, y, right, down }
         }
 
         pub fn slide(&mut self) {
@@ -24,94 +30,384 @@ mod position_mod {
     }
 }
 
-type Map = Vec<Vec<char>>;
+type Map = Grid<char>;
 
-fn read_lines_into_map(lines: Vec<String>) -> Map {
-    let number_lines = lines.len();
-    let line_length = lines[0].len();
-    println!("Found {} lines", number_lines);
-    println!("Line length = {}", line_length);
+/// How far the toboggan moves each step. Negative steps move left or up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Slope {
+    right: isize,
+    down: isize,
+}
 
-    let mut map: Map = vec![vec!['.'; line_length]; number_lines];
+impl FromStr for Slope {
+    type Err = String;
 
-    println!("Read in the map");
-    for (index, line) in lines.iter().enumerate() {
-        print!(".");
-        io::stdout().flush().unwrap();
-        let current_line: Vec<char> = line.chars().collect();
-        map[index] = current_line.clone();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expected = || format!("expected <right>,<down>, found \"{}\"", s);
+        let (right, down) = s.split_once(',').ok_or_else(expected)?;
+        let right = right.trim().parse().map_err(|_| expected())?;
+        let down = down.trim().parse().map_err(|_| expected())?;
+        Ok(Slope { right, down })
     }
-    println!("\n\nDone");
+}
 
-    return map;
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.right, self.down)
+    }
 }
 
-fn toboggan(map: &Map, movement_right: usize, movement_down: usize) -> i32 {
-    let mut pos = position_mod::Position::new(movement_right, movement_down);
-    let width = map[0].len();
-    let mut tree_count = 0;
+const PART1_SLOPE: Slope = Slope { right: 3, down: 1 };
 
-    while pos.y < map.len() {
-        if map[pos.y][pos.x] == '#' {
-            tree_count += 1;
+const PART2_SLOPES: [Slope; 5] = [
+    Slope { right: 1, down: 1 },
+    Slope { right: 3, down: 1 },
+    Slope { right: 5, down: 1 },
+    Slope { right: 7, down: 1 },
+    Slope { right: 1, down: 2 },
+];
+
+fn read_map(input: &str) -> Result<Map, AdventError> {
+    advent::progress!("Read in the map");
+    let map = Grid::parse(input, |square| match square {
+        '.' | '#' => Ok(square),
+        _ => Err(format!("expected '.' or '#', found '{}'", square)),
+    })?;
+
+    if map.is_empty() {
+        return Err(AdventError::InvalidInput("the map is empty".to_string()));
+    }
+    advent::progress!("Found {} lines", map.height());
+    advent::progress!("Line length = {}", map.width());
+
+    Ok(map)
+}
+
+/// The positions visited going down `slope` from the top left, or the bottom
+/// left for an upward slope. The map always repeats sideways, and also
+/// vertically as a torus if `wrap` says so, so positions can be off the map
+/// as if it were tiled. The path ends on leaving the map, or on getting back
+/// to the start for a path that never leaves it.
+fn path(map: &Map, slope: Slope, wrap: Wrap) -> impl Iterator<Item = (isize, isize)> + '_ {
+    let start_y = if slope.down < 0 { map.height() as isize - 1 } else { 0 };
+    let mut pos = position_mod::Position::new(start_y, slope.right, slope.down);
+    let start = map.wrap_position(pos.x, pos.y, wrap);
+    let mut back_at_start = false;
+
+    std::iter::from_fn(move || {
+        if back_at_start {
+            return None;
         }
+        map.wrap_position(pos.x, pos.y, wrap)?;
+        let here = (pos.x, pos.y);
         pos.slide();
-        if pos.x >= width {
-            // We've gone past the right boundary
-            // Work out how far past we are, and reset
-            pos.x = pos.x - width;
+        back_at_start = map.wrap_position(pos.x, pos.y, wrap) == start;
+        Some(here)
+    })
+}
+
+/// Moving a whole map width (or height, on a torus) further lands on the same
+/// squares, so shrink the steps of `slope` as far as possible without
+/// changing its direction. Shrinking the steps stops positions overflowing.
+fn shortest_steps(map: &Map, slope: Slope, wrap: Wrap) -> Slope {
+    let (width, height) = (map.width() as isize, map.height() as isize);
+    let down = match slope.down {
+        down if !wrap.vertical => down,
+        down if down < 0 => down.rem_euclid(height) - height,
+        down => down % height,
+    };
+    Slope { right: slope.right % width, down }
+}
+
+/// Count the trees hit on the path down `slope`.
+fn toboggan(map: &Map, slope: Slope, wrap: Wrap) -> usize {
+    path(map, shortest_steps(map, slope, wrap), wrap)
+        .filter(|(x, y)| map.get_wrapped(*x, *y, wrap) == Some(&'#'))
+        .count()
+}
+
+/// The tree count on each slope and their product, or an overflow error if
+/// the product doesn't fit.
+fn count_trees(map: &Map, slopes: &[Slope], wrap: Wrap) -> Result<(Vec<usize>, u64), AdventError> {
+    let counts: Vec<usize> = slopes.iter().map(|slope| toboggan(map, *slope, wrap)).collect();
+    let product = counts.iter().try_fold(1u64, |product, count| product.checked_mul(*count as u64))
+        .ok_or_else(|| AdventError::Overflow(format!("the product of the tree counts on {} slopes", slopes.len())))?;
+    Ok((counts, product))
+}
+
+/// The slopes in a `--slopes` file, one per line.
+fn parse_slopes(text: &str) -> Result<Vec<Slope>, String> {
+    let slopes = advent::input::parse_lines::<Slope>(text).map_err(|e| e.to_string())?;
+    if slopes.is_empty() {
+        return Err("there are no slopes".to_string());
+    }
+    Ok(slopes)
+}
+
+fn slopes_table(slopes: &[Slope], counts: &[usize], product: u64) -> String {
+    let rows: Vec<Vec<String>> = slopes.iter().zip(counts)
+        .map(|(slope, count)| vec![slope.right.to_string(), slope.down.to_string(), count.to_string()])
+        .collect();
+    format!("{}Product: {}\n", advent::table::render(&["Right", "Down", "Trees"], &rows), product)
+}
+
+/// Parse `a..=b`, `a..b` or a single `a` as an inclusive range.
+fn parse_range(s: &str) -> Result<RangeInclusive<isize>, String> {
+    let number = |n: &str| n.trim().parse::<isize>().map_err(|_| format!("expected a range such as 1..=5, found \"{}\"", s));
+    let range = if let Some((start, end)) = s.split_once("..=") {
+        number(start)?..=number(end)?
+    } else if let Some((start, end)) = s.split_once("..") {
+        match number(end)?.checked_sub(1) {
+            Some(end) => number(start)?..=end,
+            None => return Err(format!("the range \"{}\" is empty", s)),
         }
+    } else {
+        let n = number(s)?;
+        n..=n
+    };
+
+    if range.is_empty() {
+        return Err(format!("the range \"{}\" is empty", s));
     }
+    Ok(range)
+}
+
+/// Which end of the ranking a search is after.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rank {
+    Fewest,
+    Most,
+}
+
+// Enough to cover any sensible map without running out of memory
+const MAX_SEARCH_SLOPES: usize = 1_000_000;
+
+/// Every slope within a range of steps, to find the ones hitting the fewest
+/// or most trees.
+#[derive(Debug, Clone, PartialEq)]
+struct Search {
+    rank: Rank,
+    right: RangeInclusive<isize>,
+    down: RangeInclusive<isize>,
+    top: usize,
+}
+
+impl Search {
+    fn candidates(&self) -> Vec<Slope> {
+        self.down.clone()
+            .flat_map(|down| self.right.clone().map(move |right| Slope { right, down }))
+            .collect()
+    }
+}
+
+/// The tree count on every slope, counted across as many threads as are
+/// available. Counts are in the same order as the slopes.
+fn count_trees_parallel(map: &Map, slopes: &[Slope], wrap: Wrap) -> Vec<usize> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = slopes.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = slopes.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|slope| toboggan(map, *slope, wrap)).collect::<Vec<usize>>()))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+/// Every candidate slope and its tree count, best first. Ties are broken by
+/// the smaller down step, then the smaller right step.
+fn search_slopes(map: &Map, search: &Search, wrap: Wrap) -> Vec<(Slope, usize)> {
+    let candidates = search.candidates();
+    let counts = count_trees_parallel(map, &candidates, wrap);
+    let mut ranked: Vec<(Slope, usize)> = candidates.into_iter().zip(counts).collect();
+    ranked.sort_by(|(a, a_trees), (b, b_trees)| {
+        let by_trees = match search.rank {
+            Rank::Fewest => a_trees.cmp(b_trees),
+            Rank::Most => b_trees.cmp(a_trees),
+        };
+        by_trees.then(a.down.cmp(&b.down)).then(a.right.cmp(&b.right))
+    });
+    ranked
+}
+
+fn search_table(search: &Search, ranked: &[(Slope, usize)]) -> String {
+    let shown = &ranked[..search.top.min(ranked.len())];
+    let rows: Vec<Vec<String>> = shown.iter().enumerate()
+        .map(|(index, (slope, trees))| {
+            vec![(index + 1).to_string(), slope.right.to_string(), slope.down.to_string(), trees.to_string()]
+        })
+        .collect();
+    let rank = match search.rank {
+        Rank::Fewest => "fewest",
+        Rank::Most => "most",
+    };
+    format!(
+        "Top {} of {} slopes by {} trees\n\n{}",
+        shown.len(), ranked.len(), rank, advent::table::render(&["Rank", "Right", "Down", "Trees"], &rows)
+    )
+}
+
+#[derive(Default)]
+pub struct Day3 {
+    // Replaces the part 2 slopes, if given
+    slopes: Option<Vec<Slope>>,
+    // Replaces the part 1 slope with the best one found, if given
+    search: Option<Search>,
+    // Whether the map wraps vertically as well as sideways
+    torus: bool,
+    // Where to draw the paths over the map, if anywhere
+    render: Option<Target>,
+}
 
-    return tree_count;
+impl Day3 {
+    fn wrap(&self) -> Wrap {
+        if self.torus { Wrap::BOTH } else { Wrap::HORIZONTAL }
+    }
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let lines = advent::lines_from_file(config.filename)?;
+impl Solution for Day3 {
+    type Input = Map;
+
+    const DAY: u32 = 3;
+    const NAME: &'static str = "Toboggan Trajectory";
 
-    let map = read_lines_into_map(lines);
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        read_map(input)
+    }
 
-    let movements = vec![
-        vec![1, 1],
-        vec![3, 1],
-        vec![5, 1],
-        vec![7, 1],
-        vec![1, 2],
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        let search = match &self.search {
+            Some(search) => search,
+            None => return Ok(toboggan(input, PART1_SLOPE, self.wrap()).into()),
+        };
+
+        // The ranking is listed by `extra`
+        Ok(search_slopes(input, search, self.wrap())[0].1.into())
+    }
+
+    fn part2(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        let slopes = self.slopes.as_deref().unwrap_or(&PART2_SLOPES);
+        let (counts, product) = count_trees(input, slopes, self.wrap())?;
+        for (slope, count) in slopes.iter().zip(&counts) {
+            advent::progress!("{} Found {} trees", slope, count);
+        }
+        Ok(product.into())
+    }
+
+    const OPTIONS: &'static [DayOption] = &[
+        DayOption::value("slope", "<R,D>", "a slope of R right and D down for part 2, in place\n\
+            of the puzzle's five. Negative steps go left or up.\n\
+            Repeat for more slopes"),
+        DayOption::value("slopes", "<FILE>", "read part 2 slopes from FILE, one R,D per line"),
+        DayOption::value("search", "<RANK>", "try every slope in a range and rank them by the\n\
+            fewest or most trees. Part 1 is the best slope's trees"),
+        DayOption::value("search-right", "<RANGE>", "right steps to search, such as 0..=10 (default 1..=20)"),
+        DayOption::value("search-down", "<RANGE>", "down steps to search, such as 1..3 (default 1..=5)"),
+        DayOption::value("top", "<N>", "how many of the searched slopes to list (default 10)"),
+        DayOption::switch("torus", "wrap the map top to bottom as well as side to side"),
+        DayOption::value("render", "<FILE>", "draw the path of each --slope, or the part 1 slope,\n\
+            over the map. - prints to the terminal, or give a .ppm or\n\
+            .svg file to write an image"),
+        DayOption::switch("colour", "colour each path when rendering to the terminal"),
     ];
 
-    let mut total: u64 = 0;
-    for m in movements {
-        let result: u64 = toboggan(&map, m[0], m[1]).try_into().unwrap();
-        if total == 0 {
-            total = result;
-        } else {
-            total *= result;
+    fn configure(&mut self, options: &Options) -> Result<(), String> {
+        self.torus = options.contains("torus");
+        self.render = match options.get("render") {
+            Some(value) => Some(Target::parse(value, options.contains("colour"))?),
+            None if options.contains("colour") => return Err("--colour needs --render -".to_string()),
+            None => None,
+        };
+
+        let mut slopes = vec![];
+        if let Some(path) = options.get("slopes") {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("Problem reading {}: {}", path, e))?;
+            slopes.extend(parse_slopes(&text).map_err(|e| format!("Invalid --slopes {}: {}", path, e))?);
+        }
+        for value in options.get_all("slope") {
+            slopes.push(value.parse().map_err(|e| format!("Invalid --slope: {}", e))?);
+        }
+
+        self.slopes = if slopes.is_empty() { None } else { Some(slopes) };
+
+        let rank = match options.get("search") {
+            Some("fewest") => Rank::Fewest,
+            Some("most") => Rank::Most,
+            Some(other) => return Err(format!("Invalid --search: {} (expected fewest or most)", other)),
+            None => {
+                if let Some(name) = ["search-right", "search-down", "top"].iter().find(|name| options.contains(name)) {
+                    return Err(format!("--{} needs --search", name));
+                }
+                self.search = None;
+                return Ok(());
+            }
+        };
+        let right = parse_range(options.get("search-right").unwrap_or("1..=20"))
+            .map_err(|e| format!("Invalid --search-right: {}", e))?;
+        let down = parse_range(options.get("search-down").unwrap_or("1..=5"))
+            .map_err(|e| format!("Invalid --search-down: {}", e))?;
+        let top = match options.parse::<usize>("top")? {
+            Some(0) => return Err("Invalid --top: 0 (expected at least 1)".to_string()),
+            top => top.unwrap_or(10),
+        };
+
+        let search = Search { rank, right, down, top };
+        let total = search.right.size_hint().1.zip(search.down.size_hint().1)
+            .and_then(|(right, down)| right.checked_mul(down));
+        if !matches!(total, Some(total) if total <= MAX_SEARCH_SLOPES) {
+            return Err(format!("The search covers more than {} slopes", MAX_SEARCH_SLOPES));
         }
-        println!("{},{} Found {} trees", m[0], m[1], result);
+        self.search = Some(search);
+        Ok(())
     }
 
-    println!("Answer = {}", total);
+    fn extra(&self, input: &Self::Input, part: Option<u8>) -> Result<Option<String>, AdventError> {
+        let (part1, part2) = (part.unwrap_or(1) == 1, part.unwrap_or(2) == 2);
+        let ranked = match &self.search {
+            Some(search) if part1 => Some((search, search_slopes(input, search, self.wrap()))),
+            _ => None,
+        };
 
-    Ok(())
+        let mut sections = vec![];
+        if let Some(target) = &self.render {
+            // Draw the slopes taken by the parts being run
+            let mut slopes = vec![];
+            if part1 {
+                slopes.push(ranked.as_ref().map_or(PART1_SLOPE, |(_, ranked)| ranked[0].0));
+            }
+            if part2 {
+                for slope in self.slopes.as_deref().unwrap_or(&PART2_SLOPES) {
+                    if !slopes.contains(slope) {
+                        slopes.push(*slope);
+                    }
+                }
+            }
+            sections.push(render::render(input, &slopes, self.wrap(), target)?);
+        }
+        if let Some((search, ranked)) = &ranked {
+            sections.push(search_table(search, ranked));
+        }
+        // Tabulate the part 2 slopes when they're not the puzzle's own
+        if let Some(slopes) = self.slopes.as_ref().filter(|_| part2) {
+            let (counts, product) = count_trees(input, slopes, self.wrap())?;
+            sections.push(slopes_table(slopes, &counts, product));
+        }
+        Ok(if sections.is_empty() { None } else { Some(sections.join("\n")) })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options;
     use position_mod::Position;
 
     fn create_position() -> Position {
-        return Position::new(3, 1);
+        Position::new(0, 3, 1)
     }
 
     #[test]
-    fn test_read_lines_into_map_will_return_map() {
-        let test_data = vec![
-            ".#.".to_string(),
-            "#.#".to_string(),
-            "...".to_string(),
-            "###".to_string()
-        ];
+    fn test_read_map_will_return_map() {
+        let test_data = ".#.\n#.#\n...\n###\n";
 
         let expected = vec![
             vec!['.', '#', '.'],
@@ -119,7 +415,27 @@ mod tests {
             vec!['.', '.', '.'],
             vec!['#', '#', '#'],
         ];
-        assert_eq!(read_lines_into_map(test_data), expected);
+        assert_eq!(read_map(test_data).unwrap(), Grid::from_rows(expected).unwrap());
+    }
+
+    #[test]
+    fn test_read_map_when_malformed_will_return_err() {
+        let test_data = vec![
+            ("", 0, 0),
+            ("\n\n", 0, 0),
+            (".#.\n#x#", 2, 2),
+            (".#.\n#.", 2, 3),
+            (".#.\n#.##", 2, 4),
+        ];
+        for (input, expected_line, expected_column) in test_data {
+            match read_map(input) {
+                Err(AdventError::Parse { line, column, .. }) => {
+                    assert_eq!((line, column), (expected_line, expected_column));
+                }
+                Err(AdventError::InvalidInput(_)) => assert_eq!(expected_line, 0),
+                other => panic!("expected an error, got {:?}", other),
+            }
+        }
     }
 
     #[test]
@@ -144,28 +460,247 @@ mod tests {
 
     #[test]
     fn test_toboggan_will_count_2_trees() {
+        let test_data = ".....\n#####\n.....\n#####\n";
+
+        assert_eq!(toboggan(&read_map(test_data).unwrap(), PART1_SLOPE, Wrap::HORIZONTAL), 2);
+    }
+
+    #[test]
+    fn test_toboggan_will_count_3_trees() {
+        let test_data = ".....\n#####\n.....\n#####\n.....\n#####\n.....\n";
+
+        assert_eq!(toboggan(&read_map(test_data).unwrap(), PART1_SLOPE, Wrap::HORIZONTAL), 3);
+    }
+
+    #[test]
+    fn test_toboggan_when_slope_wider_than_map_will_wrap() {
+        let test_data = "..\n.#\n..\n.#\n";
+
+        // Moving 5 right on a map 2 wide lands on the same columns as 1 right
+        assert_eq!(toboggan(&read_map(test_data).unwrap(), Slope { right: 5, down: 1 }, Wrap::HORIZONTAL), 2);
+        assert_eq!(toboggan(&read_map(test_data).unwrap(), Slope { right: isize::MAX, down: 1 }, Wrap::HORIZONTAL), 2);
+    }
+
+    #[test]
+    fn test_toboggan_when_slope_goes_left_or_up_will_follow_it() {
         let test_data = vec![
-            ".....".to_string(),
-            "#####".to_string(),
-            ".....".to_string(),
-            "#####".to_string()
+            // Leftward from the top left wraps round to the right edge
+            ("#..\n..#\n.#.\n", Slope { right: -1, down: 1 }, 3),
+            // Upward starts from the bottom left
+            ("..#\n.#.\n#..\n", Slope { right: 1, down: -1 }, 3),
+            ("..#\n.#.\n#..\n", Slope { right: -2, down: -1 }, 3),
+            ("..#\n.#.\n#..\n", Slope { right: 1, down: isize::MIN }, 1),
         ];
+        for (map, slope, expected) in test_data {
+            assert_eq!(toboggan(&read_map(map).unwrap(), slope, Wrap::HORIZONTAL), expected, "{}", slope);
+        }
+    }
 
-        assert_eq!(toboggan(&read_lines_into_map(test_data), 3, 1), 2);
+    #[test]
+    fn test_toboggan_when_path_never_leaves_map_will_stop_at_start() {
+        let map = read_map(".#.#\n....\n").unwrap();
+        assert_eq!(toboggan(&map, Slope { right: 1, down: 0 }, Wrap::HORIZONTAL), 2);
+        assert_eq!(toboggan(&map, Slope { right: 2, down: 0 }, Wrap::HORIZONTAL), 0);
+        assert_eq!(toboggan(&map, Slope { right: 0, down: 0 }, Wrap::HORIZONTAL), 0);
     }
 
     #[test]
-    fn test_toboggan_will_count_3_trees() {
+    fn test_toboggan_when_torus_will_wrap_vertically_until_back_at_start() {
+        let map = read_map("...\n..#\n...\n").unwrap();
+        let slope = Slope { right: 1, down: 2 };
+        assert_eq!(toboggan(&map, slope, Wrap::HORIZONTAL), 0);
+        assert_eq!(toboggan(&map, slope, Wrap::BOTH), 1);
+
+        // Every square is visited once on a 2 by 3 torus before getting back
+        let map = read_map("##\n##\n##\n").unwrap();
+        assert_eq!(toboggan(&map, Slope { right: 1, down: 1 }, Wrap::BOTH), 6);
+        assert_eq!(toboggan(&map, Slope { right: -1, down: -7 }, Wrap::BOTH), 6);
+    }
+
+    #[test]
+    fn test_slope_from_str_when_malformed_will_return_err() {
+        assert_eq!("3,1".parse::<Slope>().unwrap(), Slope { right: 3, down: 1 });
+        assert_eq!(" 1, 2 ".parse::<Slope>().unwrap(), Slope { right: 1, down: 2 });
+        assert_eq!("-3,0".parse::<Slope>().unwrap(), Slope { right: -3, down: 0 });
+        for s in ["3", "3,1,1", "a,1", "1,-", ""] {
+            assert!(s.parse::<Slope>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_count_trees_when_slope_misses_every_tree_will_return_0() {
+        let map = read_map("..\n.#\n").unwrap();
+        let slopes = [Slope { right: 1, down: 1 }, Slope { right: 0, down: 1 }];
+        assert_eq!(count_trees(&map, &slopes, Wrap::HORIZONTAL).unwrap(), (vec![1, 0], 0));
+    }
+
+    #[test]
+    fn test_configure_when_slopes_given_will_replace_part2_slopes() {
+        let mut day = Day3::default();
+        day.configure(&options(&[("slope", "1,1"), ("slope", "1,2")])).unwrap();
+
+        let expected = vec![Slope { right: 1, down: 1 }, Slope { right: 1, down: 2 }];
+        assert_eq!(day.slopes, Some(expected));
+
+        let map = day.parse("...\n###\n.#.\n###\n").unwrap();
+        assert_eq!(day.part2(&map).unwrap(), Answer::Number(2));
+        let table = day.extra(&map, None).unwrap().unwrap();
+        assert!(table.ends_with("Product: 2\n"), "{}", table);
+        assert_eq!(day.extra(&map, Some(1)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_slopes_will_read_one_per_line() {
+        let expected = vec![Slope { right: 3, down: 1 }, Slope { right: -1, down: 2 }];
+        assert_eq!(parse_slopes("3,1\r\n-1,2\n").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_slopes_when_malformed_will_report_line() {
+        let err = parse_slopes("3,1\n1;0\n").unwrap_err();
+        assert!(err.contains("line 2, column 1"), "{}", err);
+        assert_eq!(parse_slopes("\n").unwrap_err(), "there are no slopes");
+    }
+
+    #[test]
+    fn test_configure_when_slopes_file_missing_will_return_err() {
+        let err = Day3::default().configure(&options(&[("slopes", "no/such/slopes.txt")])).unwrap_err();
+        assert!(err.starts_with("Problem reading no/such/slopes.txt: "), "{}", err);
+    }
+
+    #[test]
+    fn test_configure_when_torus_given_will_wrap_vertically() {
+        let mut day = Day3::default();
+        day.configure(&options(&[("slope", "1,2")])).unwrap();
+        let map = day.parse("...\n..#\n...\n").unwrap();
+        assert_eq!(day.part2(&map).unwrap(), Answer::Number(0));
+
+        day.configure(&options(&[("slope", "1,2"), ("torus", "")])).unwrap();
+        assert_eq!(day.wrap(), Wrap::BOTH);
+        assert_eq!(day.part2(&map).unwrap(), Answer::Number(1));
+    }
+
+    fn search(rank: Rank, right: RangeInclusive<isize>, down: RangeInclusive<isize>) -> Search {
+        Search { rank, right, down, top: 3 }
+    }
+
+    #[test]
+    fn test_parse_range_will_accept_each_form() {
+        assert_eq!(parse_range("1..=20").unwrap(), 1..=20);
+        assert_eq!(parse_range("0..3").unwrap(), 0..=2);
+        assert_eq!(parse_range("-3..=-1").unwrap(), -3..=-1);
+        assert_eq!(parse_range("4").unwrap(), 4..=4);
+        for s in ["1..1", "3..=2", "x..=2", "1..=", "..0"] {
+            assert!(parse_range(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_search_slopes_will_rank_fewest_and_most() {
+        let map = read_map("....\n.#.#\n..#.\n.#.#\n").unwrap();
+
+        let ranked = search_slopes(&map, &search(Rank::Most, 0..=3, 1..=2), Wrap::HORIZONTAL);
+        assert_eq!(ranked.len(), 8);
+        assert_eq!(ranked[0], (Slope { right: 1, down: 1 }, 3));
+        assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        let ranked = search_slopes(&map, &search(Rank::Fewest, 0..=3, 1..=2), Wrap::HORIZONTAL);
+        assert_eq!(ranked[0], (Slope { right: 0, down: 1 }, 0));
+        assert!(ranked.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    }
+
+    #[test]
+    fn test_count_trees_parallel_will_match_toboggan() {
+        let map = read_map("..#.#\n#.#..\n.##.#\n#...#\n..###\n#.#.#\n").unwrap();
+        let slopes = search(Rank::Fewest, -12..=12, -6..=6).candidates();
+        for wrap in [Wrap::HORIZONTAL, Wrap::BOTH] {
+            let expected: Vec<usize> = slopes.iter().map(|slope| toboggan(&map, *slope, wrap)).collect();
+            assert_eq!(count_trees_parallel(&map, &slopes, wrap), expected);
+        }
+    }
+
+    #[test]
+    fn test_search_table_will_show_top_slopes() {
+        let map = read_map("....\n.#.#\n..#.\n.#.#\n").unwrap();
+        let search = search(Rank::Most, 1..=2, 1..=1);
+        let expected = "\
+Top 2 of 2 slopes by most trees
+
+Rank | Right | Down | Trees
+-----+-------+------+------
+   1 |     1 |    1 |     3
+   2 |     2 |    1 |     0
+";
+        assert_eq!(search_table(&search, &search_slopes(&map, &search, Wrap::HORIZONTAL)), expected);
+    }
+
+    #[test]
+    fn test_configure_when_search_given_will_answer_part1_with_best() {
+        let mut day = Day3::default();
+        day.configure(&options(&[("search", "most"), ("search-right", "0..4")])).unwrap();
+        assert_eq!(day.search, Some(Search { rank: Rank::Most, right: 0..=3, down: 1..=5, top: 10 }));
+
+        let map = day.parse("....\n.#.#\n..#.\n.#.#\n").unwrap();
+        assert_eq!(day.part1(&map).unwrap(), Answer::Number(3));
+        let table = day.extra(&map, Some(1)).unwrap().unwrap();
+        assert!(table.starts_with("Top 10 of 20 slopes by most trees"), "{}", table);
+        assert_eq!(day.extra(&map, Some(2)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_configure_when_search_invalid_will_return_err() {
         let test_data = vec![
-            ".....".to_string(),
-            "#####".to_string(),
-            ".....".to_string(),
-            "#####".to_string(),
-            ".....".to_string(),
-            "#####".to_string(),
-            ".....".to_string(),
+            (vec![("search", "best")], "Invalid --search: best (expected fewest or most)"),
+            (vec![("top", "3")], "--top needs --search"),
+            (vec![("search", "most"), ("search-down", "2..=0")], "Invalid --search-down: the range \"2..=0\" is empty"),
+            (vec![("search", "most"), ("top", "0")], "Invalid --top: 0 (expected at least 1)"),
+            (vec![("search", "most"), ("search-right", "0..=1000000")], "The search covers more than 1000000 slopes"),
         ];
+        for (values, expected) in test_data {
+            assert_eq!(Day3::default().configure(&options(&values)).unwrap_err(), expected);
+        }
+    }
+
+    #[test]
+    fn test_extra_when_render_given_will_draw_slopes_of_parts_run() {
+        let mut day = Day3::default();
+        day.configure(&options(&[("render", "-")])).unwrap();
 
-        assert_eq!(toboggan(&read_lines_into_map(test_data), 3, 1), 3);
+        let map = day.parse("...\n###\n.#.\n###\n").unwrap();
+        let legend = |drawing: String| -> Vec<String> {
+            drawing.lines().filter(|line| line.starts_with("O/X")).map(String::from).collect()
+        };
+        assert_eq!(legend(day.extra(&map, Some(1)).unwrap().unwrap()), vec!["O/X 3,1: 2 trees"]);
+        assert_eq!(legend(day.extra(&map, Some(2)).unwrap().unwrap()).len(), PART2_SLOPES.len());
+        // Part 1's slope is one of part 2's, so it's only drawn once
+        assert_eq!(legend(day.extra(&map, None).unwrap().unwrap()).len(), PART2_SLOPES.len());
+
+        day.configure(&options(&[("render", "-"), ("slope", "1,2")])).unwrap();
+        assert_eq!(legend(day.extra(&map, Some(2)).unwrap().unwrap()), vec!["O/X 1,2: 1 trees"]);
+
+        assert_eq!(day.configure(&options(&[("colour", "")])).unwrap_err(), "--colour needs --render -");
+    }
+
+    #[test]
+    fn test_extra_when_render_file_unwritable_will_return_err() {
+        let mut day = Day3::default();
+        day.configure(&options(&[("render", "no/such/dir/map.svg")])).unwrap();
+
+        let map = day.parse("...\n###\n").unwrap();
+        assert!(matches!(day.extra(&map, Some(1)), Err(AdventError::Io(_))));
+        assert_eq!(day.part1(&map).unwrap(), Answer::Number(1));
+    }
+
+    #[test]
+    fn test_slopes_table_will_list_counts_and_product() {
+        let slopes = [Slope { right: 1, down: 1 }, Slope { right: 3, down: 1 }];
+        let expected = "\
+Right | Down | Trees
+------+------+------
+    1 |    1 |     2
+    3 |    1 |     7
+Product: 14
+";
+        assert_eq!(slopes_table(&slopes, &[2, 7], 14), expected);
     }
 }
@@ -0,0 +1,295 @@
+use advent::grid::{Grid, Wrap};
+use advent::AdventError;
+
+use super::{path, shortest_steps, toboggan, Map, Slope};
+
+/// Where to draw the map with the paths over it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Terminal { colour: bool },
+    Ppm(String),
+    Svg(String),
+}
+
+impl Target {
+    /// `-` for the terminal, or a file ending in `.ppm` or `.svg`.
+    pub fn parse(value: &str, colour: bool) -> Result<Target, String> {
+        let target = match value {
+            "-" => Target::Terminal { colour },
+            file if file.ends_with(".ppm") => Target::Ppm(file.to_string()),
+            file if file.ends_with(".svg") => Target::Svg(file.to_string()),
+            _ => return Err(format!("Invalid --render: {} (expected - for the terminal, or a .ppm or .svg file)", value)),
+        };
+        if colour && !matches!(target, Target::Terminal { .. }) {
+            return Err("--colour needs --render -".to_string());
+        }
+        Ok(target)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Open,
+    Tree,
+    // On the path of the slope with this index
+    Path { tree: bool, slope: usize },
+}
+
+// Enough for any real map, while keeping images to a sensible size
+const MAX_SQUARES: usize = 10_000_000;
+
+// Image pixels per square
+const SCALE: usize = 4;
+
+const ANSI_COLOURS: [u8; 6] = [31, 34, 33, 35, 36, 32];
+
+const OPEN_RGB: (u8, u8, u8) = (255, 255, 255);
+const TREE_RGB: (u8, u8, u8) = (34, 139, 34);
+const PATH_RGB: [(u8, u8, u8); 6] = [(220, 50, 47), (38, 139, 210), (203, 75, 22), (211, 54, 130), (42, 161, 152), (108, 113, 196)];
+
+/// The map tiled sideways far enough to hold every path, with the squares on
+/// each path marked. Where paths cross, the first slope's is shown.
+fn overlay(map: &Map, slopes: &[Slope], wrap: Wrap) -> Result<Grid<Cell>, AdventError> {
+    let (width, height) = (map.width() as isize, map.height() as isize);
+    let paths = slopes.iter()
+        .map(|slope| {
+            if slope.right.unsigned_abs() > MAX_SQUARES {
+                return Err(AdventError::InvalidInput(format!("the slope {} is too wide to draw", slope)));
+            }
+            // Keep the real step right, so the tiles show how far the path goes
+            let steps = Slope { right: slope.right, ..shortest_steps(map, *slope, wrap) };
+            Ok(path(map, steps, wrap).collect::<Vec<(isize, isize)>>())
+        })
+        .collect::<Result<Vec<_>, AdventError>>()?;
+
+    let tiles = paths.iter().flatten().map(|(x, _)| x.div_euclid(width));
+    let first_tile = tiles.clone().min().unwrap_or(0).min(0);
+    let last_tile = tiles.max().unwrap_or(0).max(0);
+    let columns = (last_tile - first_tile + 1) as usize * map.width();
+    if columns.saturating_mul(map.height()) > MAX_SQUARES {
+        return Err(AdventError::InvalidInput(format!(
+            "the paths cross {} copies of the map, too many to draw", last_tile - first_tile + 1
+        )));
+    }
+
+    let rows: Vec<Vec<Cell>> = map.rows()
+        .map(|row| (0..columns).map(|x| if row[x % map.width()] == '#' { Cell::Tree } else { Cell::Open }).collect())
+        .collect();
+    let mut canvas = Grid::from_rows(rows).map_err(AdventError::InvalidInput)?;
+    for (index, path) in paths.iter().enumerate() {
+        for (x, y) in path {
+            let column = (x - first_tile * width) as usize;
+            if let Some(cell) = canvas.get_mut(column, y.rem_euclid(height) as usize) {
+                if !matches!(cell, Cell::Path { .. }) {
+                    *cell = Cell::Path { tree: *cell == Cell::Tree, slope: index };
+                }
+            }
+        }
+    }
+
+    Ok(canvas)
+}
+
+fn paint(text: &str, slope: usize, colour: bool) -> String {
+    if colour {
+        format!("\x1b[1;{}m{}\x1b[0m", ANSI_COLOURS[slope % ANSI_COLOURS.len()], text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// The map as in the puzzle, with `O` for open squares and `X` for trees on
+/// a path, each slope in its own colour if `colour` is set.
+fn render_text(canvas: &Grid<Cell>, colour: bool) -> String {
+    let mut out = String::new();
+    for row in canvas.rows() {
+        for cell in row {
+            match cell {
+                Cell::Open => out.push('.'),
+                Cell::Tree => out.push('#'),
+                Cell::Path { tree, slope } => out.push_str(&paint(if *tree { "X" } else { "O" }, *slope, colour)),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn legend(slopes: &[Slope], counts: &[usize], colour: bool) -> String {
+    slopes.iter().zip(counts).enumerate()
+        .map(|(index, (slope, count))| format!("{} {}: {} trees\n", paint("O/X", index, colour), slope, count))
+        .collect()
+}
+
+fn rgb(cell: Cell) -> (u8, u8, u8) {
+    match cell {
+        Cell::Open => OPEN_RGB,
+        Cell::Tree => TREE_RGB,
+        Cell::Path { tree, slope } => {
+            let (r, g, b) = PATH_RGB[slope % PATH_RGB.len()];
+            // Trees on the path are a darker shade of the path's colour
+            if tree { (r / 2, g / 2, b / 2) } else { (r, g, b) }
+        }
+    }
+}
+
+/// A binary PPM image, each square `SCALE` pixels across.
+fn render_ppm(canvas: &Grid<Cell>) -> Vec<u8> {
+    let mut out = format!("P6\n{} {}\n255\n", canvas.width() * SCALE, canvas.height() * SCALE).into_bytes();
+    for row in canvas.rows() {
+        let line: Vec<u8> = row.iter()
+            .flat_map(|cell| {
+                let (r, g, b) = rgb(*cell);
+                [r, g, b].repeat(SCALE)
+            })
+            .collect();
+        for _ in 0..SCALE {
+            out.extend_from_slice(&line);
+        }
+    }
+    out
+}
+
+/// An SVG image, with a square for every tree and every square on a path.
+fn render_svg(canvas: &Grid<Cell>) -> String {
+    let (width, height) = (canvas.width(), canvas.height());
+    let hex = |(r, g, b): (u8, u8, u8)| format!("#{:02x}{:02x}{:02x}", r, g, b);
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+        width * SCALE, height * SCALE, width, height
+    );
+    out.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", width, height, hex(OPEN_RGB)));
+    for (y, row) in canvas.rows().enumerate() {
+        for (x, cell) in row.iter().enumerate().filter(|(_, cell)| **cell != Cell::Open) {
+            out.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>\n", x, y, hex(rgb(*cell))));
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Draw the paths down `slopes` over the map, returning the text to show
+/// or, for an image, writing it and returning where it went.
+pub fn render(map: &Map, slopes: &[Slope], wrap: Wrap, target: &Target) -> Result<String, AdventError> {
+    let canvas = overlay(map, slopes, wrap)?;
+    let counts: Vec<usize> = slopes.iter().map(|slope| toboggan(map, *slope, wrap)).collect();
+
+    let (file, image) = match target {
+        Target::Terminal { colour } => {
+            return Ok(format!("{}\n{}", render_text(&canvas, *colour), legend(slopes, &counts, *colour)));
+        }
+        Target::Ppm(file) => (file, render_ppm(&canvas)),
+        Target::Svg(file) => (file, render_svg(&canvas).into_bytes()),
+    };
+    std::fs::write(file, image)?;
+    Ok(format!("Drew {} slopes over the map in {}\n{}", slopes.len(), file, legend(slopes, &counts, false)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_3::read_map;
+
+    // The example from the puzzle, which is drawn tiled three times as
+    // the path ends in the third copy
+    const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#
+";
+
+    #[test]
+    fn test_render_text_will_match_puzzle_example() {
+        let map = read_map(EXAMPLE).unwrap();
+        let canvas = overlay(&map, &[Slope { right: 3, down: 1 }], Wrap::HORIZONTAL).unwrap();
+        let expected = "\
+O.##.........##.........##.......
+#..O#...#..#...#...#..#...#...#..
+.#....X..#..#....#..#..#....#..#.
+..#.#...#O#..#.#...#.#..#.#...#.#
+.#...##..#..X...##..#..#...##..#.
+..#.##.......#.X#.......#.##.....
+.#.#.#....#.#.#.#.O..#.#.#.#....#
+.#........#.#........X.#........#
+#.##...#...#.##...#...#.X#...#...
+#...##....##...##....##...#X....#
+.#..#...#.#.#..#...#.#.#..#...X.#
+";
+        assert_eq!(render_text(&canvas, false), expected);
+    }
+
+    #[test]
+    fn test_overlay_when_path_goes_left_will_tile_leftwards() {
+        let map = read_map("...\n.#.\n#..\n").unwrap();
+        let canvas = overlay(&map, &[Slope { right: -2, down: 1 }], Wrap::HORIZONTAL).unwrap();
+        assert_eq!(render_text(&canvas, false), "......O..\n.#..X..#.\n#.O#..#..\n");
+    }
+
+    #[test]
+    fn test_overlay_when_paths_cross_will_show_first() {
+        let map = read_map("...\n...\n").unwrap();
+        let slopes = [Slope { right: 1, down: 1 }, Slope { right: 0, down: 1 }];
+        let canvas = overlay(&map, &slopes, Wrap::HORIZONTAL).unwrap();
+        assert_eq!(canvas.get(0, 0), Some(&Cell::Path { tree: false, slope: 0 }));
+        assert_eq!(canvas.get(0, 1), Some(&Cell::Path { tree: false, slope: 1 }));
+    }
+
+    #[test]
+    fn test_overlay_when_too_wide_will_return_err() {
+        let map = read_map("..\n..\n").unwrap();
+        assert!(overlay(&map, &[Slope { right: isize::MAX, down: 1 }], Wrap::HORIZONTAL).is_err());
+        assert!(overlay(&map, &[Slope { right: 6_000_000, down: 1 }], Wrap::HORIZONTAL).is_err());
+    }
+
+    #[test]
+    fn test_render_text_when_colour_will_add_ansi_codes() {
+        let map = read_map(".#\n").unwrap();
+        let canvas = overlay(&map, &[Slope { right: 1, down: 0 }], Wrap::HORIZONTAL).unwrap();
+        assert_eq!(render_text(&canvas, true), "\x1b[1;31mO\x1b[0m\x1b[1;31mX\x1b[0m\n");
+        assert_eq!(legend(&[Slope { right: 1, down: 0 }], &[1], true), "\x1b[1;31mO/X\x1b[0m 1,0: 1 trees\n");
+    }
+
+    #[test]
+    fn test_render_ppm_will_write_scaled_pixels() {
+        let map = read_map(".#\n").unwrap();
+        let canvas = overlay(&map, &[Slope { right: 0, down: 1 }], Wrap::HORIZONTAL).unwrap();
+        let image = render_ppm(&canvas);
+
+        let header = format!("P6\n{} {}\n255\n", 2 * SCALE, SCALE);
+        assert!(image.starts_with(header.as_bytes()));
+        assert_eq!(image.len(), header.len() + 2 * SCALE * SCALE * 3);
+        let pixels = &image[header.len()..];
+        assert_eq!(&pixels[..3], &[220, 50, 47]);
+        assert_eq!(&pixels[SCALE * 3..SCALE * 3 + 3], &[34, 139, 34]);
+    }
+
+    #[test]
+    fn test_render_svg_will_draw_trees_and_path() {
+        let map = read_map("#.\n").unwrap();
+        let canvas = overlay(&map, &[Slope { right: 1, down: 1 }], Wrap::HORIZONTAL).unwrap();
+        let expected = "\
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"8\" height=\"4\" viewBox=\"0 0 2 1\" shape-rendering=\"crispEdges\">
+<rect width=\"2\" height=\"1\" fill=\"#ffffff\"/>
+<rect x=\"0\" y=\"0\" width=\"1\" height=\"1\" fill=\"#6e1917\"/>
+</svg>
+";
+        assert_eq!(render_svg(&canvas), expected);
+    }
+
+    #[test]
+    fn test_target_parse_will_pick_format() {
+        assert_eq!(Target::parse("-", true).unwrap(), Target::Terminal { colour: true });
+        assert_eq!(Target::parse("path.ppm", false).unwrap(), Target::Ppm("path.ppm".to_string()));
+        assert_eq!(Target::parse("path.svg", false).unwrap(), Target::Svg("path.svg".to_string()));
+        assert!(Target::parse("path.png", false).is_err());
+        assert_eq!(Target::parse("path.svg", true).unwrap_err(), "--colour needs --render -");
+    }
+}
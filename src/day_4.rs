@@ -1,10 +1,8 @@
-use std::io::{self, Write};
-use std::error::Error;
-use std::fs;
+#![allow(clippy::needless_return, clippy::manual_range_contains, clippy::unnecessary_filter_map, clippy::bool_assert_comparison)]
 
 use regex::Regex;
 
-use advent::Config;
+use advent::{AdventError, Answer, Solution};
 
 type FieldValidator = fn(data: &str) -> bool;
 
@@ -63,8 +61,11 @@ fn hgt_valid(data: &str) -> bool {
     // Calling unwrap will move the data, so grab it here to be used later
     let raw_cap = cap.unwrap();
 
-    // Parse the units and height
-    let height: i32 = raw_cap[1].parse().unwrap();
+    // Parse the units and height. Anything too big for an i32 is too tall.
+    let height: i32 = match raw_cap[1].parse() {
+        Ok(height) => height,
+        Err(_) => return false,
+    };
     let unit = &raw_cap[2];
 
     if unit == "cm" {
@@ -101,62 +102,79 @@ fn pid_valid(data: &str) -> bool {
     return re.is_match(data);
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let token_re = Regex::new(r"([a-z]{3}):").unwrap();
-
-    let mandatory_fields: Vec<(&str, FieldValidator)> = vec![
-        ("byr", byr_valid),
-        ("ecl", ecl_valid),
-        ("eyr", eyr_valid),
-        ("hcl", hcl_valid),
-        ("hgt", hgt_valid),
-        ("iyr", iyr_valid),
-        ("pid", pid_valid),
-    ];
-
-    // Build a list of the expected tokens
-    let expected_tokens: Vec<&str> = mandatory_fields.iter().filter_map(
-        |item| Some(item.0)).collect();
-
-    let mut valid_ids = 0;
-
-    let contents = fs::read_to_string(config.filename)?;
-    let ids = contents.split("\n\n");
-    println!("Processing IDs");
-    for id in ids {
-        let mut found_tokens: Vec<String> = token_re.captures_iter(id).filter_map(
-            |token| Some(String::from(&token[1]))
-        ).collect();
-
-        // Remove 'cid' from the found list since it's optional
-        found_tokens.retain(|x| x != "cid");
-        // Sort into natural order
-        found_tokens.sort();
-    
-        if found_tokens == expected_tokens {
-            // All mandatory tokens are present, check that they are valid
-            let mut valid = true;
-            for token in &mandatory_fields {
-                let formatted = format!(r"{}:([a-z0-9#]+)", token.0);
-                let val_re = Regex::new(formatted.as_str()).unwrap();
-                let result = val_re.captures(id);
-                let value = &result.unwrap()[1];
-                if !token.1(value) {
-                    valid = false;
-                    break;
+#[derive(Default)]
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Vec<String>;
+
+    const DAY: u32 = 4;
+    const NAME: &'static str = "Passport Processing";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        Ok(advent::input::records(input).iter().map(|record| record.join("\n")).collect())
+    }
+
+    fn part1(&self, _input: &Self::Input) -> Result<Answer, AdventError> {
+        Ok(Answer::Unsolved)
+    }
+
+    fn part2(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        let token_re = Regex::new(r"([a-z]{3}):").unwrap();
+
+        let mandatory_fields: Vec<(&str, FieldValidator)> = vec![
+            ("byr", byr_valid),
+            ("ecl", ecl_valid),
+            ("eyr", eyr_valid),
+            ("hcl", hcl_valid),
+            ("hgt", hgt_valid),
+            ("iyr", iyr_valid),
+            ("pid", pid_valid),
+        ];
+
+        // Build a list of the expected tokens
+        let expected_tokens: Vec<&str> = mandatory_fields.iter().filter_map(
+            |item| Some(item.0)).collect();
+
+        let mut valid_ids: i32 = 0;
+
+        advent::progress!("Processing IDs");
+        for id in input {
+            let mut found_tokens: Vec<String> = token_re.captures_iter(id).filter_map(
+                |token| Some(String::from(&token[1]))
+            ).collect();
+
+            // Remove 'cid' from the found list since it's optional
+            found_tokens.retain(|x| x != "cid");
+            // Sort into natural order
+            found_tokens.sort();
+
+            if found_tokens == expected_tokens {
+                // All mandatory tokens are present, check that they are valid
+                let mut valid = true;
+                for token in &mandatory_fields {
+                    let formatted = format!(r"{}:([a-z0-9#]+)", token.0);
+                    let val_re = Regex::new(formatted.as_str()).unwrap();
+                    // A field with nothing usable after the colon is invalid
+                    let valid_value = match val_re.captures(id) {
+                        Some(result) => token.1(&result[1]),
+                        None => false,
+                    };
+                    if !valid_value {
+                        valid = false;
+                        break;
+                    }
+                }
+                if valid {
+                    valid_ids += 1;
                 }
             }
-            if valid {
-                valid_ids += 1;
-            }
+            advent::progress_inline!(".");
         }
-        print!(".");
-        io::stdout().flush().unwrap();
-    }
+        advent::progress!("\nDone\n");
 
-    println!("\nDone\n\nAnswer = {}", valid_ids);
-
-    Ok(())
+        Ok(Answer::from(valid_ids))
+    }
 }
 
 #[cfg(test)]
@@ -299,6 +317,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_hgt_valid_when_huge_will_return_false() {
+        assert_eq!(hgt_valid("99999999999cm"), false);
+    }
+
+    #[test]
+    fn test_part2_when_field_has_no_value_will_not_count_it() {
+        let passports = Day4.parse("byr:1980 ecl:amb eyr:2025 hcl:#123abc hgt:180cm iyr:2015 pid:\n").unwrap();
+        assert_eq!(Day4.part2(&passports).unwrap(), Answer::Number(0));
+    }
+
     #[test]
     fn test_iyr_valid_when_letters_will_return_false() {
         assert_eq!(iyr_valid("abdc"), false);
@@ -1,7 +1,8 @@
-use std::error::Error;
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
 use std::fmt;
 
-use advent::Config;
+use advent::{AdventError, Answer, Solution};
 
 #[derive(Debug)]
 struct Seat {
@@ -36,6 +37,29 @@ fn generate_seat_id(seat: &Seat) -> i32 {
     return seat.row * 8 + seat.column;
 }
 
+/// Check a boarding pass is 7 'F' or 'B' followed by 3 'L' or 'R', so it can
+/// be given to `find_seat`.
+fn check_pass(pass: &str) -> Result<(), AdventError> {
+    for (index, c) in pass.chars().enumerate() {
+        let expected = if index < 7 { "'F' or 'B'" } else { "'L' or 'R'" };
+        let valid = match index {
+            0..=6 => c == 'F' || c == 'B',
+            7..=9 => c == 'L' || c == 'R',
+            _ => return Err(AdventError::parse(index + 1, "boarding pass is longer than 10 characters")),
+        };
+        if !valid {
+            return Err(AdventError::parse(index + 1, format!("expected {}, found '{}'", expected, c)));
+        }
+    }
+
+    let length = pass.chars().count();
+    if length < 10 {
+        return Err(AdventError::parse(length + 1, "boarding pass is shorter than 10 characters"));
+    }
+
+    Ok(())
+}
+
 fn find_seat(pass: &str) -> Seat {
     let row_def = &pass[..7];
     let mut current = BinarySearch { lower: 0, upper: 127 };
@@ -66,29 +90,45 @@ fn find_seat(pass: &str) -> Seat {
     return Seat{ row: row, column: column };
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let passes = advent::lines_from_file(config.filename)?;
+#[derive(Default)]
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vec<i32>;
 
-    let mut seats: Vec<i32> = vec![];
+    const DAY: u32 = 5;
+    const NAME: &'static str = "Binary Boarding";
 
-    for pass in passes {
-        let seat = find_seat(&pass);
-        seats.push(generate_seat_id(&seat));
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        let mut seats: Vec<i32> = advent::input::parse_lines_with(input, |pass| {
+            check_pass(pass)?;
+            Ok(generate_seat_id(&find_seat(pass)))
+        })?;
+
+        // Sort into natural order, both parts rely on it
+        seats.sort_unstable();
+        Ok(seats)
     }
 
-    // Find the missing seat. Sort into natural order first.
-    seats.sort();
-    let mut i = 1;
-    while i < seats.len() {
-        if seats[i] != seats[i-1] + 1 {
-            break;
-        }
-        i += 1;
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        // The highest seat ID
+        let highest = input.last()
+            .ok_or_else(|| AdventError::InvalidInput("there are no boarding passes".to_string()))?;
+        Ok(Answer::from(*highest))
     }
 
-    println!("The highest seat ID is {}.", seats.last().unwrap());
-    println!("My seat ID is {}.", seats[i-1] + 1);
-    Ok(())
+    fn part2(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        // Find the missing seat
+        let mut i = 1;
+        while i < input.len() {
+            if input[i] != input[i-1] + 1 {
+                return Ok(Answer::from(input[i-1] + 1));
+            }
+            i += 1;
+        }
+
+        Err(AdventError::NoSolution("there is no gap in the seat IDs".to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -120,4 +160,27 @@ mod tests {
             assert_eq!(generate_seat_id(&test.0), test.1);
         }
     }
+
+    #[test]
+    fn test_check_pass_when_malformed_will_return_column() {
+        let test_data = vec![
+            ("FBFBBFFRL", 10),
+            ("FBFBBFFRLRR", 11),
+            ("FBFBBFXRLR", 7),
+            ("FBFBBFFRLB", 10),
+        ];
+        for (pass, expected_column) in test_data {
+            match check_pass(pass) {
+                Err(AdventError::Parse { column, .. }) => assert_eq!(column, expected_column, "{}", pass),
+                other => panic!("expected a parse error for {}, got {:?}", pass, other),
+            }
+        }
+        assert!(check_pass("FBFBBFFRLR").is_ok());
+    }
+
+    #[test]
+    fn test_part2_when_no_gap_will_return_no_solution() {
+        let seats = Day5.parse("FBFBBFFRLR\nFBFBBFFRRL\n").unwrap();
+        assert!(matches!(Day5.part2(&seats), Err(AdventError::NoSolution(_))));
+    }
 }
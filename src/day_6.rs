@@ -1,18 +1,12 @@
-use std::error::Error;
-use std::fs;
+#![allow(clippy::needless_return)]
 
-use advent::Config;
+use advent::{AdventError, Answer, Solution};
 
-fn count_newlines(s: &str) -> usize {
-    s.as_bytes().iter().filter(|&&c| c == b'\n').count() + 1
-}
-
-fn count_group_answers(group: &str) -> usize {
-    let group_size = count_newlines(group);
+fn count_group_answers<S: AsRef<str>>(group: &[S]) -> usize {
+    // One line per person in the group
+    let group_size = group.len();
 
-    // Remove newlines from the char list
-    let mut questions: Vec<char> = group.chars().collect();
-    questions.retain(|x| x != &'\n');
+    let mut questions: Vec<char> = group.iter().flat_map(|person| person.as_ref().chars()).collect();
     questions.sort();
     let mut count = 0;
     let mut unique_questions: Vec<char> = questions.clone();
@@ -23,22 +17,51 @@ fn count_group_answers(group: &str) -> usize {
             count += 1;
         }
     }
-    println!("Group={} size={} count={}\n\n", group, group_size, count);
+    if advent::verbose() {
+        let people: Vec<&str> = group.iter().map(|person| person.as_ref()).collect();
+        advent::progress!("Group={} size={} count={}\n\n", people.join("\n"), group_size, count);
+    }
     return count;
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
-    let groups = contents.split("\n\n");
-    println!("Processing groups");
-    let mut sum = 0;
-    for group in groups {
-        sum += count_group_answers(group);
+#[derive(Default)]
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<Vec<String>>;
+
+    const DAY: u32 = 6;
+    const NAME: &'static str = "Custom Customs";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        // Questions are answered with the letters a to z
+        for (index, line) in advent::input::lines(input).enumerate() {
+            if let Some((column, c)) = line.chars().enumerate().find(|(_, c)| !c.is_ascii_lowercase()) {
+                return Err(AdventError::Parse {
+                    line: index + 1,
+                    column: column + 1,
+                    message: format!("expected a question from 'a' to 'z', found '{}'", c),
+                });
+            }
+        }
+
+        let groups = advent::input::records(input);
+        Ok(groups.iter().map(|group| group.iter().map(|person| person.to_string()).collect()).collect())
+    }
+
+    fn part1(&self, _input: &Self::Input) -> Result<Answer, AdventError> {
+        Ok(Answer::Unsolved)
     }
 
-    println!("\nSum of questions are {}", sum);
+    fn part2(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        advent::progress!("Processing groups");
+        let mut sum = 0;
+        for group in input {
+            sum += count_group_answers(group);
+        }
 
-    Ok(())
+        Ok(sum.into())
+    }
 }
 
 #[cfg(test)]
@@ -48,14 +71,26 @@ mod tests {
     #[test]
     fn test_count_group_answers_will_return_count() {
         let test_data = vec![
-            ("abc", 3),
-            ("a\nb\nc", 0),
-            ("ab\nac", 1),
-            ("a\na\na\na", 1),
-            ("b", 1),
+            (vec!["abc"], 3),
+            (vec!["a", "b", "c"], 0),
+            (vec!["ab", "ac"], 1),
+            (vec!["a", "a", "a", "a"], 1),
+            (vec!["b"], 1),
         ];
         for test in test_data {
-            assert_eq!(count_group_answers(test.0), test.1);
+            assert_eq!(count_group_answers(&test.0), test.1);
         }
     }
+
+    #[test]
+    fn test_parse_when_not_a_question_will_return_err() {
+        let err = Day6.parse("abc\n\nab\naC\n").unwrap_err();
+        assert!(matches!(err, AdventError::Parse { line: 4, column: 2, .. }));
+    }
+
+    #[test]
+    fn test_parse_when_crlf_will_split_groups() {
+        let groups = Day6.parse("ab\r\nac\r\n\r\nb\r\n").unwrap();
+        assert_eq!(Day6.part2(&groups).unwrap(), Answer::Number(2));
+    }
 }
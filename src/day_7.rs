@@ -1,11 +1,11 @@
-use std::error::Error;
+#![allow(clippy::needless_return, clippy::unnecessary_unwrap, clippy::needless_borrow)]
+
 use std::cmp;
 use std::fmt;
-use std::fs;
 
 use regex::Regex;
 
-use advent::Config;
+use advent::{AdventError, Answer, Solution};
 
 #[derive(Debug, Clone)]
 pub struct Contents {
@@ -35,7 +35,7 @@ impl fmt::Display for Rule {
     }
 }
 
-fn parse_rule(input: &str) -> Rule {
+fn parse_rule(input: &str) -> Result<Rule, AdventError> {
     let outer_re = Regex::new(r"^(.*) bags contain (.*)\.$").unwrap();
     let inner_re = Regex::new(r"^([0-9]+) (.*) bags?$").unwrap();
 
@@ -47,74 +47,106 @@ fn parse_rule(input: &str) -> Rule {
 
         rule.bag_colour = outer_group[1].to_string();
 
-        let inner_strings: Vec<String> = outer_group[2].split(", ").map(|x| x.to_string()).collect();
-        for inner in inner_strings {
-            let inner_re_result = inner_re.captures(&inner);
+        let contents = outer_group.get(2).unwrap();
+        if contents.as_str() == "no other bags" {
+            return Ok(rule);
+        }
+
+        // Track where each inner bag starts so errors can point at it
+        let mut column = contents.start() + 1;
+        for inner in contents.as_str().split(", ") {
+            let inner_re_result = inner_re.captures(inner);
             if inner_re_result.is_some() {
                 let inner_group = inner_re_result.unwrap();
 
                 let inner_bag = Contents {
                     colour: inner_group[2].to_string(),
-                    count: inner_group[1].parse().unwrap_or_default()
+                    count: inner_group[1].parse().map_err(|_| {
+                        AdventError::parse(column, format!("bag count {} is too large", &inner_group[1]))
+                    })?,
                 };
                 rule.inner_bags.push(inner_bag);
+            } else {
+                return Err(AdventError::parse(column, format!("expected \"<count> <colour> bags\", found \"{}\"", inner)));
             }
+            column += inner.len() + ", ".len();
         }
 
         // println!("{}", rule);
     } else {
-        println!("[ERROR] Could not parse rule: \"{}\"", input);
+        return Err(AdventError::parse(1, format!("expected \"<colour> bags contain <contents>.\", found \"{}\"", input)));
     }
 
-    return rule;
+    return Ok(rule);
 }
 
-fn parse_rules(rules: Vec<&str>) -> Vec<Rule> {
+fn parse_rules(rules: Vec<&str>) -> Result<Vec<Rule>, AdventError> {
     let mut parsed_rules: Vec<Rule> = vec![];
 
-    for line in rules {
-        if line.len() > 1 {
-            parsed_rules.push(parse_rule(line));
+    for (index, line) in rules.iter().enumerate() {
+        if !line.trim().is_empty() {
+            parsed_rules.push(parse_rule(line).map_err(|e| e.at_line(index + 1))?);
         }
     }
 
-    println!("\nParsed {} rules.", parsed_rules.len());
+    advent::progress!("\nParsed {} rules.", parsed_rules.len());
+
+    return Ok(parsed_rules);
+}
 
-    return parsed_rules;
+/// The error for bags that end up inside themselves, given the bags in the
+/// loop with each one containing the next.
+fn cycle_error(cycle: &[String]) -> AdventError {
+    let mut bags: Vec<&str> = cycle.iter().map(String::as_str).collect();
+    bags.push(bags[0]);
+    AdventError::InvalidInput(format!("bags contain each other: {}", bags.join(" -> ")))
 }
 
-fn search_for_bag(rules: &Vec<Rule>, search_colour: &str) -> Vec<String> {
+/// Every bag that can hold a `search_colour` bag, found by climbing out a bag
+/// at a time. `visiting` is the bags on the way to this one, innermost first,
+/// so a rule leading back to one of them can be reported.
+fn search_for_bag(rules: &Vec<Rule>, search_colour: &str, visiting: &mut Vec<String>) -> Result<Vec<String>, AdventError> {
     let mut matching_bags: Vec<String> = vec![];
+    visiting.push(search_colour.to_string());
 
     for rule in rules {
         for inner_bag in &rule.inner_bags {
             if inner_bag.colour == search_colour {
                 // Found a match!
                 // println!("Matched {} in {}", search_colour, rule);
+                if let Some(start) = visiting.iter().position(|colour| *colour == rule.bag_colour) {
+                    let mut cycle = visiting[start..].to_vec();
+                    cycle.reverse();
+                    return Err(cycle_error(&cycle));
+                }
                 matching_bags.push(rule.bag_colour.clone());
 
                 // Can the outer bag also be contained within another?
-                let mut temp = search_for_bag(&rules, &rule.bag_colour);
+                let mut temp = search_for_bag(&rules, &rule.bag_colour, visiting)?;
                 matching_bags.append(&mut temp);
             }
         }
     }
 
-    return matching_bags;
+    visiting.pop();
+    return Ok(matching_bags);
 }
 
-fn count_bags(rules: &Vec<Rule>, search_colour: &str) -> usize {
-    let mut matching_bags: Vec<String> = search_for_bag(rules, search_colour);
+fn count_bags(rules: &Vec<Rule>, search_colour: &str) -> Result<usize, AdventError> {
+    let mut matching_bags: Vec<String> = search_for_bag(rules, search_colour, &mut vec![])?;
 
     // Remove duplicate outer bags
     matching_bags.sort();
     matching_bags.dedup();
 
-    return matching_bags.len();
+    return Ok(matching_bags.len());
 }
 
-fn count_inner_bags(rules: &Vec<Rule>, search_colour: &str) -> i32 {
+/// How many bags a `search_colour` bag holds. `visiting` is the bags it's
+/// inside, outermost first, so a bag that holds one of them can be reported.
+fn count_inner_bags(rules: &Vec<Rule>, search_colour: &str, visiting: &mut Vec<String>) -> Result<i32, AdventError> {
     let mut count = 0;
+    visiting.push(search_colour.to_string());
 
     // println!("Searching for {}", search_colour);
     for rule in rules {
@@ -124,34 +156,50 @@ fn count_inner_bags(rules: &Vec<Rule>, search_colour: &str) -> i32 {
             // Look through the bags it continues
             for inner in &rule.inner_bags {
                 // println!("Adding {} {} bags.", inner.count, inner.colour);
-                count += inner.count;
-                count += count_inner_bags(rules, &inner.colour) * inner.count;
+                if let Some(start) = visiting.iter().position(|colour| *colour == inner.colour) {
+                    return Err(cycle_error(&visiting[start..]));
+                }
+                // Each inner bag counts itself plus everything inside it
+                let bags = count_inner_bags(rules, &inner.colour, visiting)?
+                    .checked_add(1)
+                    .and_then(|bags| bags.checked_mul(inner.count))
+                    .and_then(|bags| bags.checked_add(count));
+                count = bags.ok_or_else(|| {
+                    AdventError::Overflow(format!("too many bags inside a {} bag", search_colour))
+                })?;
             }
         }
     }
 
-    return count;
+    visiting.pop();
+    return Ok(count);
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
-    let rules: Vec<&str> = contents.split('\n').collect();
+#[derive(Default)]
+pub struct Day7;
 
-    let search_colour: &str = "shiny gold";
+const SEARCH_COLOUR: &str = "shiny gold";
 
-    let rule_list = parse_rules(rules);
+impl Solution for Day7 {
+    type Input = Vec<Rule>;
 
-    println!(
-        "Number of bags that eventually contain a {} bag is {}",
-        search_colour, count_bags(&rule_list, search_colour)
-    );
+    const DAY: u32 = 7;
+    const NAME: &'static str = "Handy Haversacks";
 
-    println!(
-        "Number of bags that a {} bag contains is {}",
-        search_colour, count_inner_bags(&rule_list, search_colour)
-    );
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        let rules: Vec<&str> = advent::input::lines(input).collect();
+        parse_rules(rules)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        // Number of bags that eventually contain a shiny gold bag
+        Ok(count_bags(input, SEARCH_COLOUR)?.into())
+    }
 
-    Ok(())
+    fn part2(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        // Number of bags that a shiny gold bag contains
+        Ok(count_inner_bags(input, SEARCH_COLOUR, &mut vec![])?.into())
+    }
 }
 
 #[cfg(test)]
@@ -164,7 +212,7 @@ mod tests {
             #[test]
             fn $name() {
                 let (input, expected) = $value;
-                let actual = parse_rule(input);
+                let actual = parse_rule(input).unwrap();
                 assert_eq!(expected.bag_colour, actual.bag_colour);
                 assert_eq!(expected.inner_bags.len(), actual.inner_bags.len());
 
@@ -277,9 +325,9 @@ mod tests {
             "dotted black bags contain no other bags.",
         ];
 
-        let parsed_rules = parse_rules(test_data);
+        let parsed_rules = parse_rules(test_data).unwrap();
 
-        assert_eq!(4, count_bags(&parsed_rules, "shiny gold"));
+        assert_eq!(4, count_bags(&parsed_rules, "shiny gold").unwrap());
     }
 
     #[test]
@@ -294,8 +342,63 @@ mod tests {
             "dark violet bags contain no other bags.",
         ];
 
-        let parsed_rules = parse_rules(test_data);
+        let parsed_rules = parse_rules(test_data).unwrap();
+
+        assert_eq!(126, count_inner_bags(&parsed_rules, "shiny gold", &mut vec![]).unwrap());
+    }
 
-        assert_eq!(126, count_inner_bags(&parsed_rules, "shiny gold"));
+    #[test]
+    fn test_count_inner_bags_when_too_many_will_return_err() {
+        let test_data = vec![
+            "shiny gold bags contain 60000 dark red bags.",
+            "dark red bags contain 60000 dark orange bags.",
+            "dark orange bags contain no other bags.",
+        ];
+
+        let parsed_rules = parse_rules(test_data).unwrap();
+
+        assert!(matches!(count_inner_bags(&parsed_rules, "shiny gold", &mut vec![]), Err(AdventError::Overflow(_))));
+    }
+
+    #[test]
+    fn test_count_bags_when_bags_contain_each_other_will_return_err() {
+        let test_data = vec![
+            "bright white bags contain 1 shiny gold bag.",
+            "shiny gold bags contain 1 dark red bag.",
+            "dark red bags contain 2 dark orange bags.",
+            "dark orange bags contain 1 shiny gold bag.",
+        ];
+
+        let parsed_rules = parse_rules(test_data).unwrap();
+
+        let expected = "bags contain each other: shiny gold -> dark red -> dark orange -> shiny gold";
+        match count_inner_bags(&parsed_rules, "shiny gold", &mut vec![]) {
+            Err(AdventError::InvalidInput(message)) => assert_eq!(message, expected),
+            other => panic!("expected a cycle error, got {:?}", other),
+        }
+        match count_bags(&parsed_rules, "shiny gold") {
+            Err(AdventError::InvalidInput(message)) => {
+                assert_eq!(message, "bags contain each other: dark red -> dark orange -> shiny gold -> dark red");
+            }
+            other => panic!("expected a cycle error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_rules_when_malformed_will_return_err() {
+        let test_data = vec![
+            ("faded blue bags contain no other bags.", 0),
+            ("light red bags hold 1 bright white bag.", 1),
+            ("light red bags contain 1 bright white bag, lots of muted yellow bags.", 44),
+            ("light red bags contain 99999999999 bright white bags.", 24),
+        ];
+        for (rule, expected_column) in test_data {
+            let result = parse_rules(vec!["", rule]);
+            match result {
+                Ok(_) => assert_eq!(expected_column, 0, "{}", rule),
+                Err(AdventError::Parse { line, column, .. }) => assert_eq!((line, column), (2, expected_column), "{}", rule),
+                Err(e) => panic!("expected a parse error for {}, got {}", rule, e),
+            }
+        }
     }
 }
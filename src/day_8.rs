@@ -1,18 +1,17 @@
-use std::error::Error;
+#![allow(clippy::redundant_field_names, clippy::unnecessary_unwrap)]
+
 use std::cmp;
 use std::fmt;
-use std::fs;
 
 use regex::Regex;
 
-use advent::Config;
+use advent::{AdventError, Answer, Solution};
 
-#[derive(Debug, PartialEq)]
-enum Operation {
+#[derive(Debug, PartialEq, Clone)]
+pub enum Operation {
     Acc,
     Jmp,
     Nop,
-    Unknown,
 }
 
 impl fmt::Display for Operation {
@@ -21,8 +20,8 @@ impl fmt::Display for Operation {
     }
 }
 
-#[derive(Debug)]
-struct Instruction {
+#[derive(Debug, Clone)]
+pub struct Instruction {
     pub op: Operation,
     pub arg: i32,
     pub executed: bool,
@@ -34,15 +33,16 @@ impl cmp::PartialEq for Instruction {
     }
 }
 
-fn add(u: usize, i: i32) -> usize {
+/// Offset `u` by `i`, or `None` if that would go below zero.
+fn add(u: usize, i: i32) -> Option<usize> {
     if i.is_negative() {
-        u - i.wrapping_abs() as u32 as usize
+        u.checked_sub(i.wrapping_abs() as u32 as usize)
     } else {
-        u + i as usize
+        Some(u + i as usize)
     }
 }
 
-fn parse_instruction(input: &str) -> Instruction {
+fn parse_instruction(input: &str) -> Result<Instruction, AdventError> {
     let re = Regex::new(r"^([a-z]{3}) ([0-9+-]+)$").unwrap();
 
     let re_result = re.captures(input);
@@ -55,26 +55,39 @@ fn parse_instruction(input: &str) -> Instruction {
             "acc" => Operation::Acc,
             "jmp" => Operation::Jmp,
             "nop" => Operation::Nop,
-            _ => Operation::Unknown,
+            _ => return Err(AdventError::parse(1, format!("unknown operation \"{}\"", op_str))),
         };
 
         let num_str: &str = &groups[2];
-        let arg: i32 = num_str.parse().unwrap();
+        let arg: i32 = num_str.parse().map_err(|_| {
+            AdventError::parse(groups.get(2).unwrap().start() + 1, format!("invalid argument \"{}\"", num_str))
+        })?;
 
-        return Instruction{ op: op, arg: arg, executed: false};
+        return Ok(Instruction{ op: op, arg: arg, executed: false});
     }
 
-    return Instruction{ op: Operation::Unknown, arg: 0, executed: false};
+    Err(AdventError::parse(1, format!("expected \"<operation> <argument>\", found \"{}\"", input)))
 }
 
-fn run_instructions(instruction_strings: &Vec<&str>) -> i32{
+fn parse_program(instruction_strings: &[&str]) -> Result<Vec<Instruction>, AdventError> {
     let mut instruction_list: Vec<Instruction> = vec![];
-    for ins_str in instruction_strings {
-        instruction_list.push(parse_instruction(ins_str));
+    for (index, ins_str) in instruction_strings.iter().enumerate() {
+        instruction_list.push(parse_instruction(ins_str).map_err(|e| e.at_line(index + 1))?);
+    }
+
+    Ok(instruction_list)
+}
+
+/// Run the program until an instruction is about to run a second time, and
+/// return the accumulator at that point.
+fn run_program(instruction_list: &mut [Instruction]) -> Result<i32, AdventError> {
+    let program_length = instruction_list.len();
+    if program_length == 0 {
+        return Err(AdventError::InvalidInput("the program is empty".to_string()));
     }
 
     let mut current_instruction = &mut instruction_list[0];
-    let mut accumulator = 0;
+    let mut accumulator: i32 = 0;
     let mut pc: usize = 0;
 
     loop {
@@ -83,54 +96,75 @@ fn run_instructions(instruction_strings: &Vec<&str>) -> i32{
         }
 
         // println!("Executing {:?}", current_instruction);
-    
+
         // Inspect the current instruction
         match current_instruction.op {
             Operation::Acc => {
                 pc += 1;
-                accumulator += current_instruction.arg;
+                accumulator = accumulator.checked_add(current_instruction.arg).ok_or_else(|| {
+                    AdventError::Overflow(format!("the accumulator overflows at instruction {}", pc))
+                })?;
             },
             Operation::Nop => {
                 pc += 1;
             },
             Operation::Jmp => {
-                pc = add(pc, current_instruction.arg);
-            },
-            Operation::Unknown => {
-                println!("Found unknown instruction! Arg={}", current_instruction.arg);
-                pc += 1;
+                pc = add(pc, current_instruction.arg).ok_or_else(|| {
+                    AdventError::InvalidInput(format!("jump {} from instruction {} leaves the program", current_instruction.arg, pc + 1))
+                })?;
             },
         };
 
         current_instruction.executed = true;
 
         // Update the current instruction
-        current_instruction = &mut instruction_list[pc];
+        if pc == program_length {
+            return Err(AdventError::NoSolution("the program finished without repeating an instruction".to_string()));
+        }
+        current_instruction = instruction_list.get_mut(pc).ok_or_else(|| {
+            AdventError::InvalidInput(format!("jump to instruction {} leaves the program", pc + 1))
+        })?;
     }
 
-    return accumulator;
+    Ok(accumulator)
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
-    let instruction_list: Vec<&str> = contents.split('\n').collect();
+#[derive(Default)]
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<Instruction>;
 
-    println!("Result = {}", run_instructions(&instruction_list));
+    const DAY: u32 = 8;
+    const NAME: &'static str = "Handheld Halting";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+        let instruction_list: Vec<&str> = advent::input::lines(input).collect();
+        parse_program(&instruction_list)
+    }
 
-    Ok(())
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+        let mut instruction_list = input.clone();
+        Ok(run_program(&mut instruction_list)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;   
 
+    fn run_instructions(instruction_strings: &[&str]) -> Result<i32, AdventError> {
+        let mut instruction_list = parse_program(instruction_strings)?;
+        run_program(&mut instruction_list)
+    }
+
     macro_rules! test_parse_instruction {
         ($($name:ident: $value:expr,)*) => {
         $(
             #[test]
             fn $name() {
                 let (input, expected) = $value;
-                let actual = parse_instruction(input);
+                let actual = parse_instruction(input).unwrap();
                 assert_eq!(expected.op, actual.op);
                 assert_eq!(expected.arg, actual.arg);
             }
@@ -190,6 +224,42 @@ mod tests {
             "acc +6",
         ];
 
-        assert_eq!(run_instructions(&test_data), 5);
+        assert_eq!(run_instructions(&test_data).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_parse_program_when_malformed_will_return_err() {
+        let test_data = vec![
+            ("foo +1", 1),
+            ("acc 1 2", 1),
+            ("acc +-1", 5),
+            ("jmp +99999999999", 5),
+        ];
+        for (instruction, expected_column) in test_data {
+            match parse_program(&["nop +0", instruction]) {
+                Err(AdventError::Parse { line, column, .. }) => assert_eq!((line, column), (2, expected_column)),
+                other => panic!("expected a parse error for {}, got {:?}", instruction, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_instructions_when_jump_leaves_program_will_return_err() {
+        for jump in &["jmp -2", "jmp +3"] {
+            let result = run_instructions(&["nop +0", jump, "acc +1"]);
+            assert!(matches!(result, Err(AdventError::InvalidInput(_))), "{}", jump);
+        }
+    }
+
+    #[test]
+    fn test_run_instructions_when_program_finishes_will_return_no_solution() {
+        let result = run_instructions(&["nop +0", "acc +1", "jmp +1"]);
+        assert!(matches!(result, Err(AdventError::NoSolution(_))));
+    }
+
+    #[test]
+    fn test_run_instructions_when_accumulator_overflows_will_return_err() {
+        let result = run_instructions(&["acc +2147483647", "acc +1", "jmp -2"]);
+        assert!(matches!(result, Err(AdventError::Overflow(_))));
     }
 }
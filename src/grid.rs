@@ -0,0 +1,217 @@
+use std::convert::TryFrom;
+
+use crate::{input, AdventError};
+
+/// Which directions a grid repeats in when reading past its edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wrap {
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+
+impl Wrap {
+    pub const NONE: Wrap = Wrap { horizontal: false, vertical: false };
+    pub const HORIZONTAL: Wrap = Wrap { horizontal: true, vertical: false };
+    pub const VERTICAL: Wrap = Wrap { horizontal: false, vertical: true };
+    pub const BOTH: Wrap = Wrap { horizontal: true, vertical: true };
+}
+
+/// Steps to the four squares sharing an edge with a square, as `(x, y)`.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Steps to all eight squares around a square, as `(x, y)`.
+pub const ALL_AROUND: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// A rectangular grid of squares, indexed by `(x, y)` from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    squares: Vec<T>,
+}
+
+/// Reduce `position` into `0..size`, repeating if `wrap` is set.
+fn wrap_coordinate(position: isize, size: usize, wrap: bool) -> Option<usize> {
+    if wrap && size > 0 {
+        Some(position.rem_euclid(size as isize) as usize)
+    } else {
+        usize::try_from(position).ok().filter(|p| *p < size)
+    }
+}
+
+impl<T> Grid<T> {
+    /// A grid from its rows, which must all be the same width.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, String> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if let Some((index, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(format!("row {} is {} wide, expected {}", index + 1, row.len(), width));
+        }
+        // A grid of empty rows has no squares at all
+        let height = if width == 0 { 0 } else { rows.len() };
+        Ok(Grid { width, height, squares: rows.into_iter().flatten().collect() })
+    }
+
+    /// Parse a grid from the lines of `input`, converting each square with
+    /// `square`. Errors are reported as by `input::parse_grid`.
+    pub fn parse<F>(input: &str, square: F) -> Result<Grid<T>, AdventError>
+    where
+        F: Fn(char) -> Result<T, String>,
+    {
+        let rows = input::parse_grid(input, square)?;
+        Grid::from_rows(rows).map_err(AdventError::InvalidInput)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.squares.is_empty()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height { self.squares.get(y * self.width + x) } else { None }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height { self.squares.get_mut(y * self.width + x) } else { None }
+    }
+
+    /// Where `(x, y)` lands in the grid, if the grid repeats as `wrap` says.
+    /// Positions off a side that doesn't wrap are `None`.
+    pub fn wrap_position(&self, x: isize, y: isize, wrap: Wrap) -> Option<(usize, usize)> {
+        let x = wrap_coordinate(x, self.width, wrap.horizontal)?;
+        let y = wrap_coordinate(y, self.height, wrap.vertical)?;
+        Some((x, y))
+    }
+
+    /// The square at `(x, y)`, if the grid repeats as `wrap` says.
+    pub fn get_wrapped(&self, x: isize, y: isize, wrap: Wrap) -> Option<&T> {
+        self.wrap_position(x, y, wrap).and_then(|(x, y)| self.get(x, y))
+    }
+
+    /// The squares one of `steps` away from `(x, y)`, such as `ORTHOGONAL` or
+    /// `ALL_AROUND`, with their positions. Steps off an edge that doesn't
+    /// wrap are skipped.
+    pub fn neighbours<'a>(
+        &'a self, x: usize, y: usize, steps: &'a [(isize, isize)], wrap: Wrap,
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+        steps.iter().filter_map(move |(dx, dy)| {
+            let position = self.wrap_position(x as isize + dx, y as isize + dy, wrap)?;
+            self.get(position.0, position.1).map(|square| (position, square))
+        })
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y < self.height { Some(&self.squares[y * self.width..(y + 1) * self.width]) } else { None }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| &self.squares[y * self.width..(y + 1) * self.width])
+    }
+
+    /// The squares in column `x`, from the top. Empty if `x` is off the grid.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let height = if x < self.width { self.height } else { 0 };
+        (0..height).map(move |y| &self.squares[y * self.width + x])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(input, |c| c.to_digit(10).ok_or_else(|| format!("unexpected '{}'", c))).unwrap()
+    }
+
+    #[test]
+    fn test_parse_will_return_grid() {
+        let grid = digits("123\r\n456\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid, Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap());
+        assert!(digits("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_when_malformed_will_report_position() {
+        let err = Grid::parse("12\n1x\n", |c| c.to_digit(10).ok_or_else(|| format!("unexpected '{}'", c))).unwrap_err();
+        assert!(matches!(err, AdventError::Parse { line: 2, column: 2, .. }));
+    }
+
+    #[test]
+    fn test_from_rows_when_ragged_will_return_err() {
+        assert_eq!(Grid::from_rows(vec![vec![1, 2], vec![3]]).unwrap_err(), "row 2 is 1 wide, expected 2");
+        let empty: Grid<u32> = Grid::from_rows(vec![vec![], vec![]]).unwrap();
+        assert_eq!((empty.width(), empty.height(), empty.rows().count()), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_get_when_off_grid_will_return_none() {
+        let grid = digits("12\n34\n");
+        assert_eq!(grid.get(1, 1), Some(&4));
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+    }
+
+    #[test]
+    fn test_get_wrapped_will_repeat_in_wrapped_directions() {
+        let grid = digits("123\n456\n");
+        let test_data = vec![
+            ((3, 0, Wrap::HORIZONTAL), Some(1)),
+            ((-1, 1, Wrap::HORIZONTAL), Some(6)),
+            // Far past the edge still lands on the right column
+            ((3 * 7 + 2, 0, Wrap::HORIZONTAL), Some(3)),
+            ((-3 * 7 - 1, 0, Wrap::HORIZONTAL), Some(3)),
+            ((0, 2, Wrap::HORIZONTAL), None),
+            ((0, -1, Wrap::VERTICAL), Some(4)),
+            ((3, 0, Wrap::VERTICAL), None),
+            ((-4, 5, Wrap::BOTH), Some(6)),
+            ((-1, 0, Wrap::NONE), None),
+        ];
+        for ((x, y, wrap), expected) in test_data {
+            assert_eq!(grid.get_wrapped(x, y, wrap).copied(), expected, "({}, {}) {:?}", x, y, wrap);
+        }
+    }
+
+    #[test]
+    fn test_get_mut_will_change_square() {
+        let mut grid = digits("12\n34\n");
+        *grid.get_mut(0, 1).unwrap() = 9;
+        assert_eq!(grid.row(1), Some(&[9, 4][..]));
+        assert!(grid.get_mut(2, 1).is_none());
+    }
+
+    #[test]
+    fn test_neighbours_will_skip_unwrapped_edges() {
+        let grid = digits("123\n456\n789\n");
+        let around: Vec<u32> = grid.neighbours(1, 1, &ALL_AROUND, Wrap::NONE).map(|(_, n)| *n).collect();
+        assert_eq!(around, vec![1, 2, 3, 4, 6, 7, 8, 9]);
+
+        let corner: Vec<((usize, usize), u32)> = grid.neighbours(0, 0, &ORTHOGONAL, Wrap::NONE).map(|(p, n)| (p, *n)).collect();
+        assert_eq!(corner, vec![((1, 0), 2), ((0, 1), 4)]);
+
+        let wrapped: Vec<u32> = grid.neighbours(0, 0, &ORTHOGONAL, Wrap::BOTH).map(|(_, n)| *n).collect();
+        assert_eq!(wrapped, vec![7, 2, 4, 3]);
+    }
+
+    #[test]
+    fn test_rows_and_columns_will_iterate_in_order() {
+        let grid = digits("123\n456\n");
+        let rows: Vec<&[u32]> = grid.rows().collect();
+        assert_eq!(rows, vec![&[1, 2, 3][..], &[4, 5, 6][..]]);
+        assert_eq!(grid.row(2), None);
+
+        let columns: Vec<Vec<u32>> = grid.columns().map(|column| column.copied().collect()).collect();
+        assert_eq!(columns, vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+        assert_eq!(grid.column(3).count(), 0);
+    }
+}
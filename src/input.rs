@@ -0,0 +1,151 @@
+use std::{fmt, str::FromStr};
+
+use crate::AdventError;
+
+/// The lines of a puzzle input. Both `\n` and `\r\n` endings are accepted and
+/// blank lines at the end are dropped.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+    input.trim_end_matches(['\n', '\r']).lines().map(|line| line.trim_end_matches('\r'))
+}
+
+/// Parse each line with `parse`, adding the line number to any parse error.
+pub fn parse_lines_with<T, F>(input: &str, parse: F) -> Result<Vec<T>, AdventError>
+where
+    F: Fn(&str) -> Result<T, AdventError>,
+{
+    lines(input).enumerate()
+        .map(|(index, line)| parse(line).map_err(|e| e.at_line(index + 1)))
+        .collect()
+}
+
+/// Parse each line, ignoring surrounding whitespace, as a `T`.
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>, AdventError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    parse_lines_with(input, |line| {
+        let value = line.trim();
+        value.parse().map_err(|e| {
+            let column = line.len() - line.trim_start().len() + 1;
+            AdventError::parse(column, format!("invalid value \"{}\": {}", value, e))
+        })
+    })
+}
+
+/// Split an input into records separated by one or more blank lines, each
+/// record being the lines that make it up.
+pub fn records(input: &str) -> Vec<Vec<&str>> {
+    let mut records = vec![];
+    let mut current = vec![];
+    for line in lines(input) {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                records.push(current);
+                current = vec![];
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        records.push(current);
+    }
+
+    records
+}
+
+/// Parse a rectangular grid of characters, converting each square with
+/// `square`. An error from `square` is reported at that square's position, as
+/// is a row that's a different width to the first.
+pub fn parse_grid<T, F>(input: &str, square: F) -> Result<Vec<Vec<T>>, AdventError>
+where
+    F: Fn(char) -> Result<T, String>,
+{
+    let mut grid: Vec<Vec<T>> = vec![];
+    for (index, line) in lines(input).enumerate() {
+        let row = line.chars().enumerate()
+            .map(|(column, c)| square(c).map_err(|message| AdventError::parse(column + 1, message)))
+            .collect::<Result<Vec<T>, AdventError>>()
+            .map_err(|e| e.at_line(index + 1))?;
+
+        if let Some(width) = grid.first().map(|first| first.len()) {
+            if row.len() != width {
+                return Err(AdventError::Parse {
+                    line: index + 1,
+                    column: row.len().min(width) + 1,
+                    message: format!("expected {} squares, found {}", width, row.len()),
+                });
+            }
+        }
+        grid.push(row);
+    }
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(c: char) -> Result<bool, String> {
+        match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(format!("unexpected '{}'", c)),
+        }
+    }
+
+    #[test]
+    fn test_lines_when_crlf_and_trailing_newlines_will_strip_them() {
+        let actual: Vec<&str> = lines("a\r\nb\r\n\r\n\n").collect();
+        assert_eq!(actual, vec!["a", "b"]);
+        assert_eq!(lines("").count(), 0);
+    }
+
+    #[test]
+    fn test_lines_will_keep_trailing_spaces_on_last_line() {
+        let actual: Vec<&str> = lines("a \nb\t \r\n\n").collect();
+        assert_eq!(actual, vec!["a ", "b\t "]);
+    }
+
+    #[test]
+    fn test_parse_lines_will_return_values() {
+        assert_eq!(parse_lines::<i64>("1\r\n -2\n3\n").unwrap(), vec![1, -2, 3]);
+    }
+
+    #[test]
+    fn test_parse_lines_when_invalid_will_report_position() {
+        let err = parse_lines::<u32>("1\n  x\n").unwrap_err();
+        assert!(matches!(err, AdventError::Parse { line: 2, column: 3, .. }));
+    }
+
+    #[test]
+    fn test_records_will_split_on_blank_lines() {
+        let input = "a\r\nb\r\n\r\nc\n\n\nd e\n\n";
+        assert_eq!(records(input), vec![vec!["a", "b"], vec!["c"], vec!["d e"]]);
+    }
+
+    #[test]
+    fn test_parse_grid_will_return_rows() {
+        let grid = parse_grid(".#\r\n#.\r\n", tree).unwrap();
+        assert_eq!(grid, vec![vec![false, true], vec![true, false]]);
+    }
+
+    #[test]
+    fn test_parse_grid_when_malformed_will_report_position() {
+        let test_data = vec![
+            ("..\n.x\n", 2, 2),
+            ("...\n..\n", 2, 3),
+            ("..\n...\n", 2, 3),
+        ];
+        for (input, expected_line, expected_column) in test_data {
+            match parse_grid(input, tree) {
+                Err(AdventError::Parse { line, column, .. }) => {
+                    assert_eq!((line, column), (expected_line, expected_column));
+                }
+                other => panic!("expected a parse error for {:?}, got {:?}", input, other),
+            }
+        }
+    }
+}
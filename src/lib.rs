@@ -1,57 +1,503 @@
 use std::{
+    convert::TryFrom,
+    env,
     error::Error,
     fmt,
-    fs::File,
-    io::{self, BufRead, BufReader},
-    path::Path,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
-pub struct Config {
-    pub day: i32,
-    pub filename: String,
+use took::Took;
+
+pub mod answers;
+mod config;
+pub mod grid;
+pub mod input;
+pub mod output;
+pub mod stats;
+pub mod table;
+
+pub use config::{Command, Config, DayOption, Format, Options, LAST_DAY};
+
+/// Environment variable naming the directory to look for puzzle inputs in.
+pub const INPUT_DIR_VAR: &str = "ADVENT_INPUT_DIR";
+
+/// Directories searched for puzzle inputs when none has been given.
+pub const DEFAULT_INPUT_DIRS: &[&str] = &["src/inputs", "inputs"];
+
+/// No input file could be found for a day.
+#[derive(Debug)]
+pub struct MissingInput {
+    pub day: u32,
+    pub searched: Vec<PathBuf>,
 }
 
-impl Config {
-    pub fn new(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 3 {
-            return Err("Day and filename arguments required");
-        }
-        if args.len() > 3 {
-            return Err("Too many arguments");
+impl fmt::Display for MissingInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let searched: Vec<String> = self.searched.iter().map(|p| p.display().to_string()).collect();
+        write!(f, "No input found for day {}, searched: {}", self.day, searched.join(", "))
+    }
+}
+
+impl Error for MissingInput {}
+
+/// The directories to search for inputs. A directory given on the command line
+/// wins over `ADVENT_INPUT_DIR`, which wins over the defaults.
+pub fn input_dirs(input_dir: Option<&str>) -> Vec<PathBuf> {
+    match input_dir.map(String::from).or_else(|| env::var(INPUT_DIR_VAR).ok()) {
+        Some(dir) => vec![PathBuf::from(dir)],
+        None => DEFAULT_INPUT_DIRS.iter().map(PathBuf::from).collect(),
+    }
+}
+
+/// Find `input_NN.txt` for a day in the first directory that has one.
+pub fn find_input(day: u32, dirs: &[PathBuf]) -> Result<PathBuf, MissingInput> {
+    let searched: Vec<PathBuf> = dirs.iter()
+        .map(|dir| dir.join(format!("input_{:02}.txt", day)))
+        .collect();
+
+    match searched.iter().find(|path| path.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(MissingInput { day, searched }),
+    }
+}
+
+static VERBOSE: AtomicBool = AtomicBool::new(true);
+
+/// Turn the progress messages days print while solving on or off.
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Like `println!`, but only when progress messages are turned on.
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if $crate::verbose() {
+            println!($($arg)*);
         }
+    };
+}
 
-        let default_day = 1;
-        let day = args[1].parse().unwrap_or(default_day);
+/// Like `print!`, flushing straight away, but only when progress messages are
+/// turned on.
+#[macro_export]
+macro_rules! progress_inline {
+    ($($arg:tt)*) => {
+        if $crate::verbose() {
+            use std::io::Write;
+            print!($($arg)*);
+            std::io::stdout().flush().unwrap();
+        }
+    };
+}
 
-        let filename = args[2].clone();
+/// The input path that means "read from stdin".
+pub const STDIN_PATH: &str = "-";
 
-        Ok(Config { day, filename })
+/// Read a whole puzzle input, from stdin if the path is `-`.
+pub fn read_input(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    if path == Path::new(STDIN_PATH) {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        Ok(contents)
+    } else {
+        fs::read_to_string(path)
     }
 }
 
 pub fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<Vec<String>> {
-    BufReader::new(File::open(filename)?).lines().collect()
+    Ok(read_input(filename)?.lines().map(String::from).collect())
+}
+
+/// The answer to one part of a day's puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    /// The part has not been solved yet.
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(n) => n.fmt(f),
+            Answer::Text(s) => s.fmt(f),
+            Answer::Unsolved => f.pad("unsolved"),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Number(n)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Self {
+        // Anything too large for an i64 is still a valid answer, just not one
+        // we can treat as a number
+        i64::try_from(n).map_or_else(|_| Answer::Text(n.to_string()), Answer::Number)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::from(n as u64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+/// A solution to a single day's puzzle.
+///
+/// The input is parsed once and the parsed form is shared by both parts.
+pub trait Solution {
+    type Input;
+
+    const DAY: u32;
+    const NAME: &'static str;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, AdventError>;
+
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError>;
+
+    fn part2(&self, _input: &Self::Input) -> Result<Answer, AdventError> {
+        Ok(Answer::Unsolved)
+    }
+
+    /// The day options this day understands, which the command line and usage
+    /// are built from.
+    const OPTIONS: &'static [DayOption] = &[];
+
+    /// Apply day options from the command line. Only options listed in
+    /// `OPTIONS` are passed on.
+    fn configure(&mut self, _options: &Options) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Output to show alongside the answers, such as a listing asked for by a
+    /// day option. It's made once per input after the parts have been timed,
+    /// and only when running a single day.
+    fn extra(&self, _input: &Self::Input, _part: Option<u8>) -> Result<Option<String>, AdventError> {
+        Ok(None)
+    }
+}
+
+/// The answer and time taken for one part of a day.
+pub struct PartReport {
+    pub part: u8,
+    pub answer: Answer,
+    pub took: Took,
+}
+
+/// Everything produced by running a day against an input.
+pub struct Report {
+    pub parse_took: Took,
+    pub parts: Vec<PartReport>,
+    /// The day's extra output, only made by `DayRunner::run_with_extra`.
+    pub extra: Option<String>,
+}
+
+/// Object safe view of a `Solution`, so days can be stored in a `Registry`.
+pub trait DayRunner {
+    fn day(&self) -> u32;
+    fn name(&self) -> &'static str;
+    /// Parse the input and solve both parts, or just `part` if given.
+    fn run(&self, input: &str, part: Option<u8>) -> Result<Report, AdventError>;
+    /// As `run`, then make the day's extra output from the parsed input.
+    fn run_with_extra(&self, input: &str, part: Option<u8>) -> Result<Report, AdventError>;
+    fn options(&self) -> &'static [DayOption];
+    /// Apply day options, failing on any the day doesn't understand.
+    fn configure(&mut self, options: &Options) -> Result<(), String>;
+}
+
+impl<S: Solution> DayRunner for S {
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn name(&self) -> &'static str {
+        S::NAME
+    }
+
+    fn options(&self) -> &'static [DayOption] {
+        S::OPTIONS
+    }
+
+    fn run(&self, input: &str, part: Option<u8>) -> Result<Report, AdventError> {
+        run_parts(self, input, part).map(|(report, _)| report)
+    }
+
+    fn run_with_extra(&self, input: &str, part: Option<u8>) -> Result<Report, AdventError> {
+        let (mut report, parsed) = run_parts(self, input, part)?;
+        report.extra = self.extra(&parsed, part)?;
+        Ok(report)
+    }
+
+    fn configure(&mut self, options: &Options) -> Result<(), String> {
+        if let Some(name) = options.names().find(|&name| S::OPTIONS.iter().all(|option| option.name != name)) {
+            return Err(format!("Day {} does not take --{}", S::DAY, name));
+        }
+        Solution::configure(self, options)
+    }
+}
+
+/// Parse the input and time each part asked for, keeping the parsed input.
+fn run_parts<S: Solution>(solution: &S, input: &str, part: Option<u8>) -> Result<(Report, S::Input), AdventError> {
+    let (parse_took, parsed) = took::took(|| solution.parse(input));
+    let parsed = parsed?;
+
+    let mut parts = vec![];
+    if part.unwrap_or(1) == 1 {
+        let (took, answer) = took::took(|| solution.part1(&parsed));
+        parts.push(PartReport { part: 1, answer: answer?, took });
+    }
+    if part.unwrap_or(2) == 2 {
+        let (took, answer) = took::took(|| solution.part2(&parsed));
+        parts.push(PartReport { part: 2, answer: answer?, took });
+    }
+
+    Ok((Report { parse_took, parts, extra: None }, parsed))
+}
+
+/// Every known day, in day order.
+pub struct Registry {
+    days: Vec<Box<dyn DayRunner>>,
+}
+
+impl Registry {
+    pub fn new(mut days: Vec<Box<dyn DayRunner>>) -> Registry {
+        days.sort_by_key(|d| d.day());
+        Registry { days }
+    }
+
+    pub fn get(&self, day: u32) -> Option<&dyn DayRunner> {
+        self.days.iter().find(|d| d.day() == day).map(|d| d.as_ref())
+    }
+
+    pub fn get_mut(&mut self, day: u32) -> Option<&mut (dyn DayRunner + 'static)> {
+        self.days.iter_mut().find(|d| d.day() == day).map(|d| d.as_mut())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn DayRunner> {
+        self.days.iter().map(|d| d.as_ref())
+    }
+
+    pub fn day_numbers(&self) -> Vec<u32> {
+        self.iter().map(|d| d.day()).collect()
+    }
+
+    /// The options of every day, for parsing the command line.
+    pub fn options(&self) -> Vec<DayOption> {
+        self.iter().flat_map(|d| d.options().iter().copied()).collect()
+    }
+
+    /// The usage message, including each day's options.
+    pub fn usage(&self) -> String {
+        let days: Vec<(u32, &[DayOption])> = self.iter().map(|d| (d.day(), d.options())).collect();
+        config::usage(&days)
+    }
 }
 
+/// Everything that can go wrong while solving a day.
 #[derive(Debug)]
-struct AdventError {
-    details: String
+pub enum AdventError {
+    Io(io::Error),
+    /// The input couldn't be parsed. `line` and `column` count from 1, with 0
+    /// meaning the position isn't known.
+    Parse { line: usize, column: usize, message: String },
+    /// The input was understood but has no answer.
+    NoSolution(String),
+    /// The input was understood but breaks the puzzle's rules.
+    InvalidInput(String),
+    /// An answer was too large to calculate.
+    Overflow(String),
 }
 
 impl AdventError {
-    fn new(msg: &str) -> AdventError {
-        AdventError{details: msg.to_string()}
+    pub fn parse(column: usize, message: impl Into<String>) -> AdventError {
+        AdventError::Parse { line: 0, column, message: message.into() }
+    }
+
+    /// Set the line a parse error happened on, for parsers that only see one
+    /// line at a time.
+    pub fn at_line(self, line: usize) -> AdventError {
+        match self {
+            AdventError::Parse { column, message, .. } => AdventError::Parse { line, column, message },
+            other => other,
+        }
     }
 }
 
 impl fmt::Display for AdventError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,"{}", self.details)
+        match self {
+            AdventError::Io(e) => write!(f, "I/O error: {}", e),
+            AdventError::Parse { line, column, message } => match (line, column) {
+                (0, 0) => write!(f, "Parse error: {}", message),
+                (0, column) => write!(f, "Parse error at column {}: {}", column, message),
+                (line, 0) => write!(f, "Parse error on line {}: {}", line, message),
+                (line, column) => write!(f, "Parse error on line {}, column {}: {}", line, column, message),
+            },
+            AdventError::NoSolution(message) => write!(f, "No solution: {}", message),
+            AdventError::InvalidInput(message) => write!(f, "Invalid input: {}", message),
+            AdventError::Overflow(message) => write!(f, "Overflow: {}", message),
+        }
     }
 }
 
 impl Error for AdventError {
-    fn description(&self) -> &str {
-        &self.details
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AdventError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AdventError {
+    fn from(e: io::Error) -> Self {
+        AdventError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    impl Solution for Echo {
+        type Input = Vec<i64>;
+
+        const DAY: u32 = 42;
+        const NAME: &'static str = "Echo";
+
+        fn parse(&self, input: &str) -> Result<Self::Input, AdventError> {
+            input.lines().enumerate().map(|(index, line)| {
+                line.parse().map_err(|_| AdventError::Parse {
+                    line: index + 1,
+                    column: 1,
+                    message: format!("{} is not a number", line),
+                })
+            }).collect()
+        }
+
+        fn part1(&self, input: &Self::Input) -> Result<Answer, AdventError> {
+            Ok(input.iter().sum::<i64>().into())
+        }
+
+        fn extra(&self, input: &Self::Input, part: Option<u8>) -> Result<Option<String>, AdventError> {
+            Ok(Some(format!("{} numbers, part {:?}", input.len(), part)))
+        }
+    }
+
+    #[test]
+    fn test_run_will_report_both_parts() {
+        let report = Echo.run("1\n2\n3\n", None).unwrap();
+        assert_eq!(report.parts.len(), 2);
+        assert_eq!(report.parts[0].answer, Answer::Number(6));
+        assert_eq!(report.parts[1].answer, Answer::Unsolved);
+    }
+
+    #[test]
+    fn test_run_when_part_given_will_report_only_that_part() {
+        let report = Echo.run("1\n2\n3\n", Some(2)).unwrap();
+        assert_eq!(report.parts.len(), 1);
+        assert_eq!(report.parts[0].part, 2);
+    }
+
+    #[test]
+    fn test_run_will_leave_out_extra() {
+        assert_eq!(Echo.run("1\n2\n3\n", None).unwrap().extra, None);
+        let report = Echo.run_with_extra("1\n2\n3\n", Some(1)).unwrap();
+        assert_eq!(report.extra.as_deref(), Some("3 numbers, part Some(1)"));
+        assert_eq!(report.parts.len(), 1);
+    }
+
+    #[test]
+    fn test_run_when_parse_fails_will_return_err() {
+        assert!(Echo.run("1\nfoo\n", None).is_err());
+    }
+
+    #[test]
+    fn test_run_when_parse_fails_will_report_position() {
+        let err = Echo.run("1\nfoo\n", None).err().unwrap();
+        assert_eq!(err.to_string(), "Parse error on line 2, column 1: foo is not a number");
+    }
+
+    #[test]
+    fn test_at_line_will_only_change_parse_errors() {
+        let err = AdventError::parse(3, "bad").at_line(7);
+        assert_eq!(err.to_string(), "Parse error on line 7, column 3: bad");
+
+        let err = AdventError::NoSolution("none".to_string()).at_line(7);
+        assert_eq!(err.to_string(), "No solution: none");
+    }
+
+    #[test]
+    fn test_configure_when_option_unknown_will_return_err() {
+        let mut options = Options::default();
+        assert!(DayRunner::configure(&mut Echo, &options).is_ok());
+
+        options.push("k", "4".to_string());
+        let err = DayRunner::configure(&mut Echo, &options).unwrap_err();
+        assert_eq!(err, "Day 42 does not take --k");
+    }
+
+    #[test]
+    fn test_registry_get_will_find_day() {
+        let registry = Registry::new(vec![Box::new(Echo)]);
+        assert_eq!(registry.get(42).map(|d| d.name()), Some("Echo"));
+        assert!(registry.get(1).is_none());
+        assert_eq!(registry.day_numbers(), vec![42]);
+        assert!(registry.options().is_empty());
+        assert!(!registry.usage().contains("Day options"));
+    }
+
+    #[test]
+    fn test_input_dirs_when_dir_given_will_use_it() {
+        assert_eq!(input_dirs(Some("elsewhere")), vec![PathBuf::from("elsewhere")]);
+    }
+
+    #[test]
+    fn test_find_input_will_return_first_match() {
+        let dirs = vec![PathBuf::from("missing"), PathBuf::from("src/inputs")];
+        assert_eq!(find_input(8, &dirs).unwrap(), Path::new("src/inputs/input_08.txt"));
+    }
+
+    #[test]
+    fn test_find_input_when_missing_will_name_searched_paths() {
+        let dirs = vec![PathBuf::from("missing"), PathBuf::from("src/inputs")];
+        let err = find_input(25, &dirs).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No input found for day 25, searched: missing/input_25.txt, src/inputs/input_25.txt"
+        );
+    }
+
+    #[test]
+    fn test_answer_from_large_u64_will_be_text() {
+        assert_eq!(Answer::from(u64::MAX), Answer::Text(u64::MAX.to_string()));
     }
 }
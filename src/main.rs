@@ -1,56 +1,430 @@
 use std::env;
 use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Duration;
 
-use took::Timer;
+use took::{Took, Timer};
 
-mod day_1;
-mod day_2;
-mod day_3;
-mod day_4;
-mod day_5;
-mod day_6;
-mod day_7;
-mod day_8;
+use advent::answers::{Answers, Verdict, ANSWERS_FILE};
+use advent::output::{Record, CSV_HEADER};
+use advent::stats::Stats;
+use advent::{AdventError, Answer, Command, Config, DayRunner, Format, Registry, Report};
 
-use advent::Config;
+// Declares each day's module and registers its solution. This is the only
+// place a new day needs adding.
+macro_rules! register_days {
+    ($($module:ident => $solution:ident,)*) => {
+        $(mod $module;)*
 
-fn run_help(config: Config) -> Result<(), Box<dyn Error>> {
-    println!("Unrecognised day: {}", config.day);
-    println!("Current supported days: [1, 2, 3, 4, 5, 6, 7, 8]");
-    Ok(())
+        fn registry() -> Registry {
+            Registry::new(vec![$(Box::new($module::$solution::default()),)*])
+        }
+    };
+}
+
+register_days! {
+    day_1 => Day1,
+    day_2 => Day2,
+    day_3 => Day3,
+    day_4 => Day4,
+    day_5 => Day5,
+    day_6 => Day6,
+    day_7 => Day7,
+    day_8 => Day8,
+}
+
+/// Day options for tests, as (name, value) pairs with an empty value for a
+/// switch.
+#[cfg(test)]
+fn options(pairs: &[(&str, &str)]) -> advent::Options {
+    let mut options = advent::Options::default();
+    for (name, value) in pairs {
+        options.push(name, value.to_string());
+    }
+    options
+}
+
+fn run_help(registry: &Registry) {
+    println!("{}", registry.usage());
+}
+
+fn run_list(registry: &Registry) {
+    let rows: Vec<Vec<String>> = registry.iter()
+        .map(|runner| vec![runner.day().to_string(), runner.name().to_string()])
+        .collect();
+    print!("{}", advent::table::render(&["Day", "Name"], &rows));
+}
+
+/// Find the day asked for, exiting if it hasn't been solved yet.
+fn find_day(registry: &Registry, day: u32) -> &dyn DayRunner {
+    registry.get(day).unwrap_or_else(|| {
+        println!("Day {} is not available", day);
+        println!("Current supported days: {:?}", registry.day_numbers());
+        process::exit(1);
+    })
+}
+
+/// The days a command applies to, either the one given with --day or all of
+/// them.
+fn selected_days<'a>(registry: &'a Registry, config: &Config) -> Vec<&'a dyn DayRunner> {
+    match config.day {
+        Some(day) => vec![find_day(registry, day)],
+        None => registry.iter().collect(),
+    }
+}
+
+/// The day's input file found in the input directories.
+fn default_input(config: &Config, day: u32) -> Result<PathBuf, Box<dyn Error>> {
+    Ok(advent::find_input(day, &advent::input_dirs(config.input_dir.as_deref()))?)
+}
+
+/// The input files for a day, either as given with --input or the default one,
+/// exiting if there are none.
+fn input_paths(config: &Config, day: u32) -> Vec<PathBuf> {
+    if !config.inputs.is_empty() {
+        return config.inputs.iter().map(PathBuf::from).collect();
+    }
+
+    match default_input(config, day) {
+        Ok(path) => vec![path],
+        Err(e) => {
+            match config.format {
+                Format::Text => println!("Application error: {}", e),
+                _ => print_records(config, day, "", &Err(e)),
+            }
+            process::exit(1);
+        }
+    }
+}
+
+/// Write a day's result as machine readable records, in the requested format.
+fn print_records(config: &Config, day: u32, input: &str, result: &Result<Report, Box<dyn Error>>) {
+    for record in Record::from_result(day, input, result) {
+        match config.format {
+            Format::Json => println!("{}", record.to_json()),
+            Format::Csv => println!("{}", record.to_csv()),
+            Format::Text => unreachable!("text output is not written as records"),
+        }
+    }
+}
+
+fn input_name(path: &Path) -> String {
+    if path == Path::new(advent::STDIN_PATH) {
+        "<stdin>".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+fn read_input(path: &Path) -> Result<String, Box<dyn Error>> {
+    advent::read_input(path).map_err(|e| format!("Problem reading {}: {}", input_name(path), e).into())
+}
+
+fn run_day(runner: &dyn DayRunner, path: &Path, part: Option<u8>) -> Result<Report, Box<dyn Error>> {
+    Ok(runner.run(&read_input(path)?, part)?)
+}
+
+fn run_single(registry: &Registry, config: &Config) {
+    let runner = find_day(registry, config.day.expect("run requires --day"));
+    let text = config.format == Format::Text;
+
+    if text {
+        println!("Running Day {}: {}", runner.day(), runner.name());
+    } else if config.format == Format::Csv {
+        println!("{}", CSV_HEADER);
+    }
+
+    let timer = Timer::new();
+    let mut failed = false;
+    for path in input_paths(config, runner.day()) {
+        if text {
+            println!("\nInput: {}", input_name(&path));
+        }
+
+        let result = read_input(&path).and_then(|input| Ok(runner.run_with_extra(&input, config.part)?));
+        failed |= result.is_err();
+        if !text {
+            // Keep extra output apart from the records
+            if let Some(extra) = result.as_ref().ok().and_then(|report| report.extra.as_ref()) {
+                eprintln!("{}", extra);
+            }
+            print_records(config, runner.day(), &input_name(&path), &result);
+            continue;
+        }
+
+        match result {
+            Ok(report) => {
+                if let Some(extra) = report.extra {
+                    println!("{}", extra);
+                }
+                for part in report.parts {
+                    println!("Part {}: {} <took {}>", part.part, part.answer, part.took);
+                }
+            }
+            Err(e) => println!("Application error: {}", e),
+        }
+    }
+
+    if text {
+        println!("\n<Finished, took {}>", timer.took());
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// Time each phase of a day over repeated runs. The input is read once up
+/// front and progress messages are turned off, so neither is measured.
+fn bench_day(runner: &dyn DayRunner, path: &Path, config: &Config) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
+    let input = read_input(path)?;
+
+    advent::set_verbose(false);
+    let reports: Result<Vec<Report>, AdventError> = (0..config.warmup + config.iterations)
+        .map(|_| runner.run(&input, config.part))
+        .collect();
+    advent::set_verbose(true);
+    let reports = reports?.split_off(config.warmup);
+
+    let mut phases = vec![(
+        "parse".to_string(),
+        reports.iter().map(|r| *r.parse_took.as_std()).collect::<Vec<Duration>>(),
+    )];
+    for (index, part) in reports[0].parts.iter().enumerate() {
+        phases.push((
+            format!("part {}", part.part),
+            reports.iter().map(|r| *r.parts[index].took.as_std()).collect(),
+        ));
+    }
+    phases.push((
+        "total".to_string(),
+        reports.iter().map(|r| {
+            *r.parse_took.as_std() + r.parts.iter().map(|p| *p.took.as_std()).sum::<Duration>()
+        }).collect(),
+    ));
+
+    let show = |d: Duration| Took::from_std(d).to_string();
+    Ok(phases.into_iter().map(|(phase, samples)| {
+        let stats = Stats::new(&samples).expect("at least one iteration");
+        vec![phase, show(stats.min), show(stats.median), show(stats.mean), show(stats.p95), show(stats.max)]
+    }).collect())
+}
+
+fn run_bench(registry: &Registry, config: &Config) {
+    let runner = find_day(registry, config.day.expect("bench requires --day"));
+
+    println!(
+        "Benchmarking Day {}: {} ({} warmup, {} timed iterations)",
+        runner.day(), runner.name(), config.warmup, config.iterations
+    );
+
+    let mut failed = false;
+    for path in input_paths(config, runner.day()) {
+        println!("\nInput: {}", input_name(&path));
+        match bench_day(runner, &path, config) {
+            Ok(rows) => {
+                let headers = ["Phase", "Min", "Median", "Mean", "p95", "Max"];
+                println!("\n{}", advent::table::render(&headers, &rows));
+            }
+            Err(e) => {
+                println!("Application error: {}", e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn run_all(registry: &Registry, config: &Config) {
+    let text = config.format == Format::Text;
+    let mut rows = vec![];
+    let mut total = Duration::default();
+    let mut failed = false;
+
+    if config.format == Format::Csv {
+        println!("{}", CSV_HEADER);
+    }
+
+    for runner in registry.iter() {
+        if text {
+            println!("Running Day {}: {}", runner.day(), runner.name());
+        }
+
+        let timer = Timer::new();
+        let path = default_input(config, runner.day());
+        let name = path.as_ref().map(|p| input_name(p)).unwrap_or_default();
+        let result = path.and_then(|path| run_day(runner, &path, config.part));
+        total += timer.took().into_std();
+
+        if !text {
+            failed |= result.is_err();
+            print_records(config, runner.day(), &name, &result);
+            continue;
+        }
+
+        match result {
+            Ok(report) => {
+                for part in report.parts {
+                    rows.push(vec![
+                        runner.day().to_string(),
+                        part.part.to_string(),
+                        part.answer.to_string(),
+                        part.took.to_string(),
+                    ]);
+                }
+            }
+            Err(e) => {
+                failed = true;
+                rows.push(vec![
+                    runner.day().to_string(),
+                    "-".to_string(),
+                    format!("error: {}", e),
+                    "-".to_string(),
+                ]);
+            }
+        }
+    }
+
+    if text {
+        rows.push(vec![
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            Took::from_std(total).to_string(),
+        ]);
+
+        println!("\n{}", advent::table::render(&["Day", "Part", "Answer", "Time"], &rows));
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn run_check(registry: &Registry, config: &Config) {
+    let mut answers = Answers::load(ANSWERS_FILE).unwrap_or_else(|err| {
+        println!("Problem reading {}: {}", ANSWERS_FILE, err);
+        process::exit(1);
+    });
+
+    let mut rows = vec![];
+    let (mut passed, mut failed, mut missing, mut unsolved, mut recorded) = (0, 0, 0, 0, 0);
+
+    for runner in selected_days(registry, config) {
+        println!("Running Day {}: {}", runner.day(), runner.name());
+
+        let result = default_input(config, runner.day())
+            .and_then(|path| run_day(runner, &path, config.part));
+        let report = match result {
+            Ok(report) => report,
+            Err(e) => {
+                failed += 1;
+                rows.push(vec![
+                    runner.day().to_string(),
+                    "-".to_string(),
+                    "FAIL".to_string(),
+                    format!("error: {}", e),
+                ]);
+                continue;
+            }
+        };
+
+        for part in report.parts {
+            // A part nobody has solved yet has nothing to check or record
+            if part.answer == Answer::Unsolved {
+                unsolved += 1;
+                rows.push(vec![
+                    runner.day().to_string(),
+                    part.part.to_string(),
+                    "UNSOLVED".to_string(),
+                    "-".to_string(),
+                ]);
+                continue;
+            }
+
+            let verdict = answers.check(runner.day(), part.part, &part.answer);
+            let (status, mut detail) = match &verdict {
+                Verdict::Pass => ("PASS", part.answer.to_string()),
+                Verdict::Fail { expected, actual } => {
+                    ("FAIL", format!("expected {}, got {}", expected, actual))
+                }
+                Verdict::Missing => ("MISSING", part.answer.to_string()),
+            };
+
+            match verdict {
+                Verdict::Pass => passed += 1,
+                _ if config.update => {
+                    answers.set(runner.day(), part.part, &part.answer);
+                    detail.push_str(" (recorded)");
+                    recorded += 1;
+                }
+                Verdict::Fail { .. } => failed += 1,
+                Verdict::Missing => missing += 1,
+            }
+
+            rows.push(vec![
+                runner.day().to_string(),
+                part.part.to_string(),
+                status.to_string(),
+                detail,
+            ]);
+        }
+    }
+
+    println!("\n{}", advent::table::render(&["Day", "Part", "Status", "Detail"], &rows));
+    println!(
+        "{} passed, {} failed, {} missing, {} unsolved, {} recorded",
+        passed, failed, missing, unsolved, recorded
+    );
+
+    if recorded > 0 {
+        if let Err(e) = answers.save(ANSWERS_FILE) {
+            println!("Problem writing {}: {}", ANSWERS_FILE, e);
+            process::exit(1);
+        }
+        println!("Updated {}", ANSWERS_FILE);
+    }
+
+    if failed > 0 {
+        process::exit(1);
+    }
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    let config = Config::new(&args).unwrap_or_else(|err| {
+    let mut registry = registry();
+    let config = Config::new(&args, &registry.options()).unwrap_or_else(|err| {
         println!("Problem parsing arguments: {}", err);
+        println!("Run `advent help` for usage");
         process::exit(1);
     });
 
-    println!("Advent 2020");
-    println!("===========\n");
-    println!("Running Day {}", config.day);
-
-    type DayRunner = fn(Config) -> Result<(), Box<dyn Error>>;
-
-    let runner: DayRunner = match config.day {
-        1 => day_1::run,
-        2 => day_2::run,
-        3 => day_3::run,
-        4 => day_4::run,
-        5 => day_5::run,
-        6 => day_6::run,
-        7 => day_7::run,
-        8 => day_8::run,
-        _ => run_help,
-    };
+    if let Some(runner) = config.day.and_then(|day| registry.get_mut(day)) {
+        runner.configure(&config.options).unwrap_or_else(|err| {
+            println!("Problem parsing arguments: {}", err);
+            process::exit(1);
+        });
+    }
 
-    let timer = Timer::new();
-    if let Err(e) = runner(config) {
-        println!("Application error: {}", e);
-        process::exit(1);
+    if config.format != Format::Text {
+        // Keep stdout to just the records
+        advent::set_verbose(false);
+    } else if !matches!(config.command, Command::Help | Command::List) {
+        println!("Advent 2020");
+        println!("===========\n");
+    }
+
+    match config.command {
+        Command::Run => run_single(&registry, &config),
+        Command::All => run_all(&registry, &config),
+        Command::Bench => run_bench(&registry, &config),
+        Command::Check => run_check(&registry, &config),
+        Command::List => run_list(&registry),
+        Command::Help => run_help(&registry),
     }
-    println!("\n<Finished, took {}>", timer.took());
 }
@@ -0,0 +1,156 @@
+use std::{error::Error, time::Duration};
+
+use crate::{Answer, Report};
+
+/// Column names for `Record::to_csv`.
+pub const CSV_HEADER: &str = "day,part,answer,duration_ns,input,error";
+
+/// One machine readable result: the answer to a part of a day, or the error
+/// that stopped the day running.
+#[derive(Debug, PartialEq)]
+pub struct Record {
+    pub day: u32,
+    pub part: Option<u8>,
+    pub answer: Option<Answer>,
+    pub duration: Option<Duration>,
+    pub input: String,
+    pub error: Option<String>,
+}
+
+impl Record {
+    /// The records for running a day against an input, one per part or a
+    /// single record if the day failed.
+    pub fn from_result(day: u32, input: &str, result: &Result<Report, Box<dyn Error>>) -> Vec<Record> {
+        match result {
+            Ok(report) => report.parts.iter().map(|part| Record {
+                day,
+                part: Some(part.part),
+                answer: Some(part.answer.clone()),
+                duration: Some(*part.took.as_std()),
+                input: input.to_string(),
+                error: None,
+            }).collect(),
+            Err(e) => vec![Record {
+                day,
+                part: None,
+                answer: None,
+                duration: None,
+                input: input.to_string(),
+                error: Some(e.to_string()),
+            }],
+        }
+    }
+
+    /// A single line JSON object.
+    pub fn to_json(&self) -> String {
+        let answer = match &self.answer {
+            Some(Answer::Number(n)) => n.to_string(),
+            Some(Answer::Text(s)) => json_string(s),
+            Some(Answer::Unsolved) | None => "null".to_string(),
+        };
+        format!(
+            "{{\"day\":{},\"part\":{},\"answer\":{},\"duration_ns\":{},\"input\":{},\"error\":{}}}",
+            self.day,
+            self.part.map_or("null".to_string(), |p| p.to_string()),
+            answer,
+            self.duration.map_or("null".to_string(), |d| d.as_nanos().to_string()),
+            json_string(&self.input),
+            self.error.as_deref().map_or("null".to_string(), json_string),
+        )
+    }
+
+    /// A CSV row matching `CSV_HEADER`. Missing values are left empty.
+    pub fn to_csv(&self) -> String {
+        let answer = match &self.answer {
+            Some(Answer::Unsolved) | None => String::new(),
+            Some(answer) => answer.to_string(),
+        };
+        let fields = [
+            self.day.to_string(),
+            self.part.map_or(String::new(), |p| p.to_string()),
+            answer,
+            self.duration.map_or(String::new(), |d| d.as_nanos().to_string()),
+            self.input.clone(),
+            self.error.clone().unwrap_or_default(),
+        ];
+        let quoted: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        quoted.join(",")
+    }
+}
+
+/// Quote a string for JSON, escaping anything that needs it.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(answer: Answer) -> Record {
+        Record {
+            day: 8,
+            part: Some(1),
+            answer: Some(answer),
+            duration: Some(Duration::from_micros(15)),
+            input: "src/inputs/input_08.txt".to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_to_json_will_write_object() {
+        assert_eq!(
+            record(Answer::Number(1087)).to_json(),
+            "{\"day\":8,\"part\":1,\"answer\":1087,\"duration_ns\":15000,\"input\":\"src/inputs/input_08.txt\",\"error\":null}"
+        );
+    }
+
+    #[test]
+    fn test_to_json_will_escape_strings() {
+        let json = record(Answer::Text("a \"b\"\n\u{1}".to_string())).to_json();
+        assert!(json.contains("\"answer\":\"a \\\"b\\\"\\n\\u0001\""));
+    }
+
+    #[test]
+    fn test_to_json_when_unsolved_will_write_null() {
+        assert!(record(Answer::Unsolved).to_json().contains("\"answer\":null"));
+    }
+
+    #[test]
+    fn test_to_csv_will_quote_fields() {
+        let mut r = record(Answer::Text("a,b".to_string()));
+        r.input = "say \"hi\"".to_string();
+        assert_eq!(r.to_csv(), "8,1,\"a,b\",15000,\"say \"\"hi\"\"\",");
+    }
+
+    #[test]
+    fn test_from_result_when_err_will_return_error_record() {
+        let records = Record::from_result(3, "-", &Err("bad input".into()));
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].part, None);
+        assert_eq!(records[0].error, Some("bad input".to_string()));
+        assert_eq!(records[0].to_csv(), "3,,,,-,bad input");
+    }
+}
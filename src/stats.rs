@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+/// Summary statistics over a set of timing samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Summarise the samples, or `None` if there aren't any.
+    pub fn new(samples: &[Duration]) -> Option<Stats> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let count = sorted.len();
+        let median = if count.is_multiple_of(2) {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2
+        } else {
+            sorted[count / 2]
+        };
+        let total: Duration = sorted.iter().sum();
+
+        Some(Stats {
+            min: sorted[0],
+            median,
+            mean: total / count as u32,
+            p95: sorted[percentile_index(count, 95)],
+            max: sorted[count - 1],
+        })
+    }
+}
+
+/// Nearest rank index of a percentile within `count` sorted samples.
+fn percentile_index(count: usize, percentile: usize) -> usize {
+    let rank = (percentile * count).div_ceil(100);
+    rank.max(1) - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_millis(*v)).collect()
+    }
+
+    #[test]
+    fn test_new_when_empty_will_return_none() {
+        assert_eq!(Stats::new(&[]), None);
+    }
+
+    #[test]
+    fn test_new_will_summarise_samples() {
+        let stats = Stats::new(&millis(&[5, 1, 4, 2, 3])).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.mean, Duration::from_millis(3));
+        assert_eq!(stats.p95, Duration::from_millis(5));
+        assert_eq!(stats.max, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_new_when_even_count_will_average_median() {
+        let stats = Stats::new(&millis(&[1, 2, 3, 10])).unwrap();
+        assert_eq!(stats.median, Duration::from_micros(2500));
+    }
+
+    #[test]
+    fn test_p95_will_use_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).collect();
+        let stats = Stats::new(&millis(&samples)).unwrap();
+        assert_eq!(stats.p95, Duration::from_millis(95));
+    }
+}
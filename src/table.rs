@@ -0,0 +1,59 @@
+/// Render rows as a plain text table with each column padded to its widest
+/// cell. Columns where every non-empty cell is a number are right aligned.
+pub fn render(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    let mut numeric = vec![true; headers.len()];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+            numeric[i] &= cell.is_empty() || cell.parse::<f64>().is_ok();
+        }
+    }
+
+    let header_cells: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    let mut out = render_row(&header_cells, &widths, &numeric);
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    out.push_str(&rule.join("-+-"));
+    out.push('\n');
+    for row in rows {
+        out.push_str(&render_row(row, &widths, &numeric));
+    }
+
+    out
+}
+
+fn render_row(cells: &[String], widths: &[usize], numeric: &[bool]) -> String {
+    let padded: Vec<String> = cells.iter().enumerate().map(|(i, cell)| {
+        if numeric[i] {
+            format!("{:>width$}", cell, width = widths[i])
+        } else {
+            format!("{:<width$}", cell, width = widths[i])
+        }
+    }).collect();
+
+    let mut line = padded.join(" | ");
+    // Don't leave trailing padding on the last column
+    line.truncate(line.trim_end().len());
+    line.push('\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_will_align_columns() {
+        let rows = vec![
+            vec!["1".to_string(), "foo".to_string()],
+            vec!["10".to_string(), "12".to_string()],
+        ];
+        let expected = "\
+Day | Answer
+----+-------
+  1 | foo
+ 10 | 12
+";
+        assert_eq!(render(&["Day", "Answer"], &rows), expected);
+    }
+}